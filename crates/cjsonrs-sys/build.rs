@@ -4,6 +4,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cjson_lib_path = std::env::var("CJSON_LIB_PATH");
     let vendored = std::env::var("CARGO_FEATURE_VENDORED");
     let std = std::env::var("CARGO_FEATURE_STD");
+    let no_libc_heap = std::env::var("CARGO_FEATURE_NO_LIBC_HEAP");
     let kind = if vendored.is_ok() { "static" } else { "dylib" };
 
     println!("cargo::rerun-if-env-changed=CJSON_SRC_PATH");
@@ -18,13 +19,24 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         println!("cargo::rustc-link-lib={kind}=cJSON");
     } else if vendored.is_ok() {
         println!("cargo::metadata=CJSON_LIB_PATH={cjson_src_path}");
-        cc::Build::new()
+        let mut build = cc::Build::new();
+        build
             .file(format!("{}/cJSON.c", cjson_src_path))
             .file(format!("{}/cJSON_Utils.c", cjson_src_path))
             .files(glob::glob(&format!("{cjson_src_path}/cJSON*.c"))?.flatten())
             .static_flag(true)
-            .include(&cjson_include_path)
-            .try_compile("cJSON")?;
+            .include(&cjson_include_path);
+
+        // cJSON falls back to the C heap until hooks are installed, so its
+        // references are redirected to the stubs defined in `lib.rs`.
+        if no_libc_heap.is_ok() {
+            build
+                .define("malloc", "cjsonrs_sys_malloc")
+                .define("free", "cjsonrs_sys_free")
+                .define("realloc", "cjsonrs_sys_realloc");
+        }
+
+        build.try_compile("cJSON")?;
     }
 
     let mut builder = bindgen::Builder::default()
@@ -14,3 +14,24 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
+
+/// Stand-ins for the C heap, used by the vendored cJSON until hooks are
+/// installed with [`cJSON_InitHooks`]. Allocations fail and frees are ignored,
+/// as nothing is ever allocated through them.
+#[cfg(feature = "no-libc-heap")]
+mod no_libc_heap {
+    use core::ffi::c_void;
+
+    #[no_mangle]
+    extern "C" fn cjsonrs_sys_malloc(_size: usize) -> *mut c_void {
+        core::ptr::null_mut()
+    }
+
+    #[no_mangle]
+    extern "C" fn cjsonrs_sys_free(_ptr: *mut c_void) {}
+
+    #[no_mangle]
+    extern "C" fn cjsonrs_sys_realloc(_ptr: *mut c_void, _size: usize) -> *mut c_void {
+        core::ptr::null_mut()
+    }
+}
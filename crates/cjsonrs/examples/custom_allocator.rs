@@ -1,28 +1,33 @@
+use core::ffi::c_void;
+
 use cjsonrs::cjson;
+use cjsonrs::hooks::Allocator;
 
 extern "C" {
-    fn malloc(size: usize) -> *mut core::ffi::c_void;
-    fn free(ptr: *mut core::ffi::c_void);
+    fn malloc(size: usize) -> *mut c_void;
+    fn free(ptr: *mut c_void);
 }
 
-unsafe extern "C" fn malloc_fn(size: usize) -> *mut core::ffi::c_void {
-    let ptr = malloc(size);
-    println!("Called malloc! size={size} addr={ptr:?}");
-    ptr
-}
+struct LoggingAllocator;
+
+unsafe impl Allocator for LoggingAllocator {
+    fn malloc(&self, size: usize) -> *mut c_void {
+        let ptr = unsafe { malloc(size) };
+        println!("Called malloc! size={size} addr={ptr:?}");
+        ptr
+    }
 
-unsafe extern "C" fn free_fn(ptr: *mut core::ffi::c_void) {
-    println!("Called free! addr={ptr:?}");
-    free(ptr)
+    unsafe fn free(&self, ptr: *mut c_void) {
+        println!("Called free! addr={ptr:?}");
+        free(ptr)
+    }
 }
 
+static ALLOCATOR: LoggingAllocator = LoggingAllocator;
+
 fn main() {
-    let mut hooks = cjsonrs_sys::cJSON_Hooks {
-        malloc_fn: Some(malloc_fn),
-        free_fn: Some(free_fn),
-    };
     println!("Initializing hooks");
-    unsafe { cjsonrs_sys::cJSON_InitHooks(&mut hooks as _) };
+    cjsonrs::hooks::install(&ALLOCATOR).expect("failed to install hooks");
 
     println!("Creating object");
     let hello_world = cjson!({
@@ -30,4 +35,7 @@ fn main() {
     })
     .expect("failed to construct cJSON");
     println!("{hello_world}");
+
+    // Replacing the hooks while values are alive is refused.
+    assert!(cjsonrs::hooks::install(&ALLOCATOR).is_err());
 }
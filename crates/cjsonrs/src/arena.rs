@@ -0,0 +1,71 @@
+use super::hooks;
+use super::hooks::Usage;
+use super::CJson;
+use super::CJsonRef;
+use super::Error;
+
+/// A bump region to parse short-lived JSON documents into.
+///
+/// Every node and string of a document parsed with [`Arena::parse`] is
+/// allocated from the buffer of the arena, without calling the allocator.
+/// Nothing is freed individually: parsing another document reuses the whole
+/// buffer, and the memory is released at once when the buffer is.
+///
+/// Arenas are handled by the [hooks](super::hooks), so they require hooks to
+/// be installed (e.g. through the `rust-alloc` feature).
+///
+/// # Example usage
+///
+/// ```
+/// use cjsonrs::Arena;
+///
+/// # if cfg!(feature = "rust-alloc") {
+/// let mut buffer = [0; 4096];
+/// let mut arena = Arena::new(&mut buffer);
+///
+/// for message in [r#"{"id": 1}"#, r#"{"id": 2}"#] {
+///     let request = arena.parse(message.as_bytes()).unwrap();
+///     assert!(request.pointer(c"/id").is_some_and(|id| id.is_number()));
+/// }
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Arena<'buf> {
+    buffer: &'buf mut [u8],
+}
+
+impl<'buf> Arena<'buf> {
+    /// Creates an arena allocating from `buffer`.
+    #[inline(always)]
+    pub fn new(buffer: &'buf mut [u8]) -> Self {
+        Self { buffer }
+    }
+
+    /// Returns the size of the buffer of the arena.
+    #[inline(always)]
+    pub fn capacity(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Parses a byte slice into the arena, reusing the memory of any document
+    /// previously parsed into it.
+    ///
+    /// The document is borrowed from the arena, so it can be read but not
+    /// modified. It does not borrow from `s`. Values copied out of it, e.g.
+    /// with [`CJsonRef::duplicate`], are allocated as usual.
+    ///
+    /// # Errors
+    ///
+    /// This function returns [`Error::Allocation`] if the document does not
+    /// fit in the arena, [`Error::HooksRequired`] if no hooks are installed,
+    /// and a [`ParseError`](super::ParseError) if the input is not valid JSON.
+    pub fn parse(&mut self, s: &[u8]) -> Result<&CJsonRef<'static>, Error> {
+        // Installs the default hooks, if any, before checking for them.
+        let _usage = Usage::acquire();
+        // The arena owns the memory, so the tree must never be deleted.
+        let parse = || CJson::from_slice(s).map(CJson::into_raw_parts);
+        let ptr = hooks::with_arena(self.buffer, parse)??;
+
+        Ok(unsafe { CJsonRef::from_ptr(ptr.as_ptr()) })
+    }
+}
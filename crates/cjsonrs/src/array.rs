@@ -173,6 +173,12 @@ impl<'a, T: AsRef<CJsonRef<'a>>> Display for CJsonArray<T> {
     }
 }
 
+impl<'json, T: AsRef<CJsonRef<'json>>> AsRef<CJsonRef<'json>> for CJsonArray<T> {
+    fn as_ref(&self) -> &CJsonRef<'json> {
+        self.inner.as_ref()
+    }
+}
+
 impl<'json> TryFrom<CJson<'json>> for CJsonArray<CJson<'json>> {
     type Error = Error;
 
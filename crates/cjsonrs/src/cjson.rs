@@ -11,16 +11,24 @@ use core::borrow::Borrow;
 use core::ffi::CStr;
 use core::fmt::Debug;
 use core::fmt::Display;
+use core::fmt::Write;
 use core::marker::PhantomData;
 use core::ops::Deref;
 use core::ops::DerefMut;
 use core::ptr::NonNull;
 use core::str::FromStr;
 
+#[cfg(all(feature = "std", target_has_atomic = "ptr"))]
+use super::hooks;
+use super::hooks::Usage;
+use super::print::NumberBuffer;
 use super::CJsonArray;
 use super::CJsonObject;
 use super::CJsonRef;
+use super::CJsonString;
+use super::Documents;
 use super::Error;
+use super::ParseOptions;
 
 /// A safe and owned wrapper around [`cjsonrs_sys::cJSON`].
 ///
@@ -45,12 +53,13 @@ impl CJson<'_> {
     /// This function returns an error if the allocation fails.
     #[inline(always)]
     pub fn null() -> Result<Self, Error> {
+        let _usage = Usage::acquire();
         let cjson = unsafe { cjsonrs_sys::cJSON_CreateNull() };
 
         if let Some(ptr) = NonNull::new(cjson) {
             Ok(unsafe { CJson::from_raw_parts(ptr, PhantomData::<&'_ ()>) })
         } else {
-            Err(Error::Parse)
+            Err(Error::Allocation)
         }
     }
 
@@ -62,12 +71,13 @@ impl CJson<'_> {
     #[inline(always)]
     pub fn bool(b: impl Into<bool>) -> Result<Self, Error> {
         let b = b.into();
+        let _usage = Usage::acquire();
         let cjson = unsafe { cjsonrs_sys::cJSON_CreateBool(b as _) };
 
         if let Some(ptr) = NonNull::new(cjson) {
             Ok(unsafe { CJson::from_raw_parts(ptr, PhantomData::<&'_ ()>) })
         } else {
-            Err(Error::Parse)
+            Err(Error::Allocation)
         }
     }
 
@@ -78,11 +88,168 @@ impl CJson<'_> {
     /// This function returns an error if the allocation fails.
     #[inline(always)]
     pub fn number(n: impl Into<f64>) -> Result<Self, Error> {
+        let _usage = Usage::acquire();
         let cjson = unsafe { cjsonrs_sys::cJSON_CreateNumber(n.into()) };
         if let Some(ptr) = NonNull::new(cjson) {
             Ok(unsafe { CJson::from_raw_parts(ptr, PhantomData::<&'_ ()>) })
         } else {
-            Err(Error::Parse)
+            Err(Error::Allocation)
+        }
+    }
+
+    /// Constructs a new [`CJson`] value representing an `i64` number.
+    ///
+    /// cJSON stores numbers as doubles, which represent every integer up to
+    /// 2^53 exactly. See [`CJson::i64_lossless`] for larger integers.
+    ///
+    /// # Example usage
+    ///
+    /// ```
+    /// use cjsonrs::CJson;
+    /// use cjsonrs::Error;
+    ///
+    /// assert_eq!(CJson::i64(1 << 53)?.as_i64(), Some(1 << 53));
+    /// assert_eq!(CJson::i64((1 << 53) + 1), Err(Error::InexactNumber));
+    /// # Ok::<(), Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function returns [`Error::InexactNumber`] if `n` cannot be
+    /// represented exactly as a double, or an error if the allocation fails.
+    #[inline(always)]
+    pub fn i64(n: i64) -> Result<Self, Error> {
+        let double = n as f64;
+        if double as i128 != i128::from(n) {
+            return Err(Error::InexactNumber);
+        }
+        Self::number(double)
+    }
+
+    /// Constructs a new [`CJson`] value representing a `u64` number.
+    ///
+    /// See [`CJson::i64`] for more information.
+    ///
+    /// # Errors
+    ///
+    /// This function returns [`Error::InexactNumber`] if `n` cannot be
+    /// represented exactly as a double, or an error if the allocation fails.
+    #[inline(always)]
+    pub fn u64(n: u64) -> Result<Self, Error> {
+        let double = n as f64;
+        if double as i128 != i128::from(n) {
+            return Err(Error::InexactNumber);
+        }
+        Self::number(double)
+    }
+
+    /// Constructs a new [`CJson`] value representing an `i64` number, storing
+    /// integers that doubles cannot represent exactly as raw number tokens.
+    ///
+    /// Raw tokens are printed verbatim, so the integer round-trips bit-exact
+    /// through serialization. They are not numbers to cJSON (i.e.
+    /// [`CJsonRef::as_number`] returns [`None`]), but they are read back by
+    /// [`CJsonRef::as_i64`] and [`CJsonRef::as_u64`].
+    ///
+    /// # Example usage
+    ///
+    /// ```
+    /// use cjsonrs::CJson;
+    ///
+    /// let id = CJson::i64_lossless(i64::MAX)?;
+    /// assert_eq!(id.to_string(), "9223372036854775807");
+    /// assert_eq!(id.as_i64(), Some(i64::MAX));
+    /// # Ok::<(), cjsonrs::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the allocation fails.
+    #[inline(always)]
+    pub fn i64_lossless(n: i64) -> Result<Self, Error> {
+        match Self::i64(n) {
+            Err(Error::InexactNumber) => Self::raw_integer(n),
+            result => result,
+        }
+    }
+
+    /// Constructs a new [`CJson`] value representing a `u64` number, storing
+    /// integers that doubles cannot represent exactly as raw number tokens.
+    ///
+    /// See [`CJson::i64_lossless`] for more information.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the allocation fails.
+    #[inline(always)]
+    pub fn u64_lossless(n: u64) -> Result<Self, Error> {
+        match Self::u64(n) {
+            Err(Error::InexactNumber) => Self::raw_integer(n),
+            result => result,
+        }
+    }
+
+    /// Constructs a raw value holding the decimal representation of `n`.
+    fn raw_integer(n: impl Display) -> Result<Self, Error> {
+        let mut buffer = NumberBuffer::new();
+        write!(buffer, "{n}\0").map_err(|_| Error::Allocation)?;
+        let token = CStr::from_bytes_with_nul(buffer.as_bytes()).map_err(|_| Error::Allocation)?;
+        Self::raw_unvalidated(token)
+    }
+
+    /// Constructs a new [`CJson`] value holding a pre-serialized JSON
+    /// fragment.
+    ///
+    /// Raw values are printed verbatim, which allows splicing cached payloads
+    /// into larger documents without parsing them into trees. The fragment is
+    /// parsed once to make sure it is a single valid JSON value. See
+    /// [`CJson::raw_unvalidated`] to skip this check.
+    ///
+    /// # Example usage
+    ///
+    /// ```
+    /// use cjsonrs::cjson;
+    /// use cjsonrs::CJson;
+    ///
+    /// let cached = CJson::raw(c"{\"temperature\": 21.5}")?;
+    /// let message = cjson!({ c"id" => 1, c"reading" => cached })?;
+    /// assert_eq!(message.to_string(), r#"{"id":1,"reading":{"temperature": 21.5}}"#);
+    ///
+    /// assert!(CJson::raw(c"[1, 2").is_err());
+    /// # Ok::<(), cjsonrs::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function returns a [`ParseError`](super::ParseError) if the
+    /// fragment is not a single valid JSON value, or an error if the
+    /// allocation fails.
+    #[inline(always)]
+    pub fn raw(fragment: impl AsRef<CStr>) -> Result<Self, Error> {
+        let fragment = fragment.as_ref();
+        ParseOptions::new()
+            .require_end_of_input(true)
+            .parse(fragment.to_bytes())?;
+        Self::raw_unvalidated(fragment)
+    }
+
+    /// Constructs a new [`CJson`] value holding a JSON fragment, without
+    /// checking that it is valid JSON.
+    ///
+    /// The fragment is printed verbatim, so an invalid fragment results in
+    /// invalid documents. See [`CJson::raw`] for more information.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the allocation fails.
+    #[inline(always)]
+    pub fn raw_unvalidated(fragment: impl AsRef<CStr>) -> Result<Self, Error> {
+        let _usage = Usage::acquire();
+        let cjson = unsafe { cjsonrs_sys::cJSON_CreateRaw(fragment.as_ref().as_ptr()) };
+        if let Some(ptr) = NonNull::new(cjson) {
+            Ok(unsafe { CJson::from_raw_parts(ptr, PhantomData::<&'_ ()>) })
+        } else {
+            Err(Error::Allocation)
         }
     }
 
@@ -93,11 +260,50 @@ impl CJson<'_> {
     /// This function returns an error if the allocation fails.
     #[inline(always)]
     pub fn string(s: impl AsRef<CStr>) -> Result<Self, Error> {
+        let _usage = Usage::acquire();
         let cjson = unsafe { cjsonrs_sys::cJSON_CreateString(s.as_ref().as_ptr()) };
         if let Some(ptr) = NonNull::new(cjson) {
             Ok(unsafe { CJson::from_raw_parts(ptr, PhantomData::<&'_ ()>) })
         } else {
-            Err(Error::Parse)
+            Err(Error::Allocation)
+        }
+    }
+
+    /// Constructs a new [`CJson`] value representing a string value from a
+    /// UTF-8 string.
+    ///
+    /// # Example usage
+    ///
+    /// ```
+    /// use cjsonrs::CJson;
+    /// use cjsonrs::Error;
+    ///
+    /// assert_eq!(CJson::from_str_value("hello")?, CJson::string(c"hello")?);
+    /// assert_eq!(CJson::from_str_value("a\0b"), Err(Error::InteriorNul(1)));
+    /// # Ok::<(), Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function returns [`Error::InteriorNul`] if `s` contains a nul
+    /// byte, as C strings cannot hold them, or an error if the allocation
+    /// fails.
+    #[inline(always)]
+    pub fn from_str_value(s: &str) -> Result<Self, Error> {
+        let s = CJsonString::copy_from_str(s)?;
+
+        let _usage = Usage::acquire();
+        let cjson = unsafe { cjsonrs_sys::cJSON_CreateStringReference(c"".as_ptr()) };
+        let Some(ptr) = NonNull::new(cjson) else {
+            return Err(Error::Allocation);
+        };
+
+        // The node takes ownership of the copy, so it is no longer a reference.
+        unsafe {
+            let node = ptr.as_ptr();
+            (*node).valuestring = s.into_raw().as_ptr();
+            (*node).type_ = cjsonrs_sys::cJSON_String as _;
+            Ok(CJson::from_raw_parts(ptr, PhantomData::<&'_ ()>))
         }
     }
 
@@ -108,11 +314,12 @@ impl CJson<'_> {
     /// This function returns an error if the allocation fails.
     #[inline(always)]
     pub fn array() -> Result<Self, Error> {
+        let _usage = Usage::acquire();
         let cjson = unsafe { cjsonrs_sys::cJSON_CreateArray() };
         if let Some(ptr) = NonNull::new(cjson) {
             Ok(unsafe { CJson::from_raw_parts(ptr, PhantomData::<&'_ ()>) })
         } else {
-            Err(Error::Parse)
+            Err(Error::Allocation)
         }
     }
 
@@ -123,11 +330,12 @@ impl CJson<'_> {
     /// This function returns an error if the allocation fails.
     #[inline(always)]
     pub fn object() -> Result<Self, Error> {
+        let _usage = Usage::acquire();
         let cjson = unsafe { cjsonrs_sys::cJSON_CreateObject() };
         if let Some(ptr) = NonNull::new(cjson) {
             Ok(unsafe { CJson::from_raw_parts(ptr, PhantomData::<&'_ ()>) })
         } else {
-            Err(Error::Parse)
+            Err(Error::Allocation)
         }
     }
 
@@ -136,33 +344,72 @@ impl CJson<'_> {
     /// # Errors
     ///
     /// This function returns an error if the input is not valid JSON or if
-    /// allocation fails.
+    /// allocation fails. Parse errors report where cJSON stopped parsing, see
+    /// [`ParseError`](super::ParseError).
     #[inline(always)]
     pub fn from_c_str(s: impl AsRef<CStr>) -> Result<Self, Error> {
-        let cjson = unsafe { cjsonrs_sys::cJSON_Parse(s.as_ref().as_ptr()) };
-
-        if let Some(ptr) = NonNull::new(cjson) {
-            Ok(unsafe { CJson::from_raw_parts(ptr, PhantomData::<&'_ ()>) })
-        } else {
-            Err(Error::Parse)
-        }
+        Self::from_slice(s.as_ref().to_bytes())
     }
 
     /// Parses a byte slice into a [`CJson`] value.
     ///
+    /// Anything following the first JSON document is ignored. See
+    /// [`ParseOptions`] for stricter parsing.
+    ///
     /// # Errors
     ///
     /// This function returns an error if the input is not valid JSON or if
-    /// allocation fails.
+    /// allocation fails. Parse errors report where cJSON stopped parsing, see
+    /// [`ParseError`](super::ParseError).
     #[inline(always)]
     pub fn from_slice(s: &[u8]) -> Result<Self, Error> {
-        let cjson = unsafe { cjsonrs_sys::cJSON_ParseWithLength(s.as_ptr() as _, s.len()) };
+        let (cjson, _) = ParseOptions::new().parse(s)?;
+        Ok(cjson)
+    }
 
-        if let Some(ptr) = NonNull::new(cjson) {
-            Ok(unsafe { CJson::from_raw_parts(ptr, PhantomData::<&'_ ()>) })
-        } else {
-            Err(Error::Parse)
-        }
+    /// Parses a byte slice into a [`CJson`] value, allowing cJSON to allocate
+    /// at most `max_bytes` bytes while parsing.
+    ///
+    /// This bounds the memory taken by untrusted payloads. The limit is
+    /// enforced by the [hooks](super::hooks), so it requires hooks to be
+    /// installed (e.g. through the `rust-alloc` feature). Bytes are counted as
+    /// requested by cJSON, without the overhead of the allocator, and only
+    /// allocations from the current thread count towards the limit.
+    ///
+    /// # Example usage
+    ///
+    /// ```
+    /// use cjsonrs::CJson;
+    /// use cjsonrs::Error;
+    ///
+    /// # if cfg!(feature = "rust-alloc") {
+    /// let cjson = CJson::from_slice_with_limit(br#"{"id": 1}"#, 1024).unwrap();
+    /// assert_eq!(cjson.to_string(), r#"{"id":1}"#);
+    ///
+    /// let payload = format!("[{}0]", "0,".repeat(1000));
+    /// let result = CJson::from_slice_with_limit(payload.as_bytes(), 1024);
+    /// assert_eq!(result, Err(Error::Allocation));
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function returns [`Error::Allocation`] if parsing would exceed
+    /// `max_bytes`, [`Error::HooksRequired`] if no hooks are installed, and a
+    /// [`ParseError`](super::ParseError) if the input is not valid JSON.
+    #[cfg(all(feature = "std", target_has_atomic = "ptr"))]
+    #[inline(always)]
+    pub fn from_slice_with_limit(s: &[u8], max_bytes: usize) -> Result<Self, Error> {
+        // Installs the default hooks, if any, before checking for them.
+        let _usage = Usage::acquire();
+        hooks::with_limit(max_bytes, || Self::from_slice(s))?
+    }
+
+    /// Returns an iterator over the JSON documents of a byte slice, such as
+    /// newline-delimited JSON. See [`Documents`] for more information.
+    #[inline(always)]
+    pub fn iter_documents(s: &[u8]) -> Documents<'_> {
+        Documents::new(s)
     }
 }
 
@@ -175,6 +422,7 @@ impl<'json> CJson<'json> {
     /// This function returns an error if the allocation fails.
     #[inline(always)]
     pub fn string_reference(s: &'json CStr) -> Result<Self, Error> {
+        let _usage = Usage::acquire();
         let cjson = unsafe { cjsonrs_sys::cJSON_CreateStringReference(s.as_ptr()) };
         if let Some(ptr) = NonNull::new(cjson) {
             Ok(unsafe { CJson::from_raw_parts(ptr, PhantomData::<&'json ()>) })
@@ -211,12 +459,14 @@ impl<'json> CJson<'json> {
     ///
     /// This function is unsafe because it does not check if the input is a
     /// valid [`cjsonrs_sys::cJSON`] value. The caller must ensure that the
-    /// input is valid.
+    /// input is valid and that it was allocated with the current
+    /// [hooks](super::hooks).
     #[inline(always)]
     pub unsafe fn from_raw_parts<T>(cjson: NonNull<cjsonrs_sys::cJSON>, _: PhantomData<T>) -> Self
     where
         T: 'json,
     {
+        Usage::acquire().forget();
         Self {
             cjson,
             _phantom: PhantomData,
@@ -235,13 +485,17 @@ impl<'json> CJson<'json> {
     pub fn into_raw_parts(self) -> NonNull<cjsonrs_sys::cJSON> {
         let ptr = self.cjson;
         core::mem::forget(self);
+        unsafe { Usage::release() };
         ptr
     }
 }
 
 impl Drop for CJson<'_> {
     fn drop(&mut self) {
-        unsafe { cjsonrs_sys::cJSON_Delete(self.cjson.as_ptr()) };
+        unsafe {
+            cjsonrs_sys::cJSON_Delete(self.cjson.as_ptr());
+            Usage::release();
+        }
     }
 }
 
@@ -327,9 +581,11 @@ generate_try_from_impl!(CString, string);
 generate_try_from_impl!(i8, number);
 generate_try_from_impl!(i16, number);
 generate_try_from_impl!(i32, number);
+generate_try_from_impl!(i64, i64);
 generate_try_from_impl!(u8, number);
 generate_try_from_impl!(u16, number);
 generate_try_from_impl!(u32, number);
+generate_try_from_impl!(u64, u64);
 generate_try_from_impl!(f32, number);
 generate_try_from_impl!(f64, number);
 
@@ -10,7 +10,9 @@ use core::fmt::Debug;
 use core::fmt::Display;
 use core::marker::PhantomData;
 use core::ptr::NonNull;
+use core::str::Utf8Error;
 
+use super::hooks::Usage;
 use super::CJson;
 use super::CJsonArray;
 use super::CJsonObject;
@@ -24,6 +26,9 @@ use super::Error;
 #[repr(transparent)]
 pub struct CJsonRef<'json>(cjsonrs_sys::cJSON, PhantomData<&'json ()>);
 
+// Only this flag describes the key, every other one describes the value.
+const KEY_FLAGS: core::ffi::c_int = cjsonrs_sys::cJSON_StringIsConst as _;
+
 impl<'json> CJsonRef<'json> {
     /// Returns a pointer to the underlying [`cjsonrs_sys::cJSON`] object.
     #[inline(always)]
@@ -118,6 +123,19 @@ impl<'json> CJsonRef<'json> {
         b != 0
     }
 
+    /// Returns `true` if the underlying [`cjsonrs_sys::cJSON`] object is a
+    /// raw value, i.e. a JSON fragment that is printed verbatim.
+    ///
+    /// Besides the values created with [`CJson::raw`], this includes the
+    /// large integers created by [`CJson::i64_lossless`] and
+    /// [`CJson::u64_lossless`].
+    #[inline(always)]
+    pub fn is_raw(&self) -> bool {
+        let ptr = self.as_ptr();
+        let b = unsafe { cjsonrs_sys::cJSON_IsRaw(ptr) };
+        b != 0
+    }
+
     /// Returns the underlying [`cjsonrs_sys::cJSON`] object as a boolean, if
     /// possible.
     #[inline(always)]
@@ -142,6 +160,120 @@ impl<'json> CJsonRef<'json> {
         Some(unsafe { cjsonrs_sys::cJSON_GetNumberValue(ptr) })
     }
 
+    /// Returns the underlying [`cjsonrs_sys::cJSON`] object as an `i32`, if
+    /// it is an integer within range.
+    ///
+    /// Unlike the `valueint` field of cJSON, which saturates and truncates
+    /// the fractional part, this returns [`None`] for fractional, non-finite
+    /// and out of range numbers.
+    ///
+    /// # Example usage
+    ///
+    /// ```
+    /// use cjsonrs::CJson;
+    ///
+    /// assert_eq!(CJson::number(-42)?.as_i32(), Some(-42));
+    /// assert_eq!(CJson::number(2.5)?.as_i32(), None);
+    /// assert_eq!(CJson::number(1e10)?.as_i32(), None);
+    /// # Ok::<(), cjsonrs::Error>(())
+    /// ```
+    #[inline(always)]
+    pub fn as_i32(&self) -> Option<i32> {
+        self.as_integer()?.try_into().ok()
+    }
+
+    /// Returns the underlying [`cjsonrs_sys::cJSON`] object as a `u32`, if
+    /// it is an integer within range.
+    ///
+    /// See [`CJsonRef::as_i32`] for more information.
+    #[inline(always)]
+    pub fn as_u32(&self) -> Option<u32> {
+        self.as_integer()?.try_into().ok()
+    }
+
+    /// Returns the underlying [`cjsonrs_sys::cJSON`] object as a `usize`, if
+    /// it is an integer within range, such as an index or a length.
+    ///
+    /// See [`CJsonRef::as_i32`] for more information.
+    #[inline(always)]
+    pub fn as_usize(&self) -> Option<usize> {
+        self.as_integer()?.try_into().ok()
+    }
+
+    /// Returns the underlying [`cjsonrs_sys::cJSON`] object as an `i64`, if
+    /// it is an integer within range.
+    ///
+    /// Besides numbers, this accepts the raw integer tokens created by
+    /// [`CJson::i64_lossless`] and [`CJson::u64_lossless`].
+    ///
+    /// # Example usage
+    ///
+    /// ```
+    /// use cjsonrs::CJson;
+    ///
+    /// assert_eq!(CJson::number(-42)?.as_i64(), Some(-42));
+    /// assert_eq!(CJson::number(1.5)?.as_i64(), None);
+    /// assert_eq!(CJson::i64_lossless(i64::MIN)?.as_i64(), Some(i64::MIN));
+    /// # Ok::<(), cjsonrs::Error>(())
+    /// ```
+    #[inline(always)]
+    pub fn as_i64(&self) -> Option<i64> {
+        self.as_integer()?.try_into().ok()
+    }
+
+    /// Returns the underlying [`cjsonrs_sys::cJSON`] object as a `u64`, if
+    /// it is an integer within range.
+    ///
+    /// See [`CJsonRef::as_i64`] for more information.
+    #[inline(always)]
+    pub fn as_u64(&self) -> Option<u64> {
+        self.as_integer()?.try_into().ok()
+    }
+
+    /// Returns the integer held by a number or a raw integer token.
+    #[inline(always)]
+    fn as_integer(&self) -> Option<i128> {
+        if let Some(n) = self.as_number() {
+            // Within range, only integral doubles survive the round-trip.
+            const LIMIT: f64 = i128::MAX as f64;
+            let integer = n as i128;
+            ((-LIMIT..LIMIT).contains(&n) && integer as f64 == n).then_some(integer)
+        } else {
+            // Only accept JSON integers, which `str::parse` is more lenient
+            // about (e.g. `+5` or `007`).
+            let token = self.as_raw()?.to_str().ok()?;
+            let digits = token.strip_prefix('-').unwrap_or(token);
+            let json = match digits.as_bytes() {
+                [b'0'] => true,
+                [b'1'..=b'9', rest @ ..] => rest.iter().all(u8::is_ascii_digit),
+                _ => false,
+            };
+            json.then(|| token.parse().ok())?
+        }
+    }
+
+    /// Returns the JSON fragment held by the underlying
+    /// [`cjsonrs_sys::cJSON`] object, if it is a raw value.
+    ///
+    /// # Example usage
+    ///
+    /// ```
+    /// use cjsonrs::CJson;
+    ///
+    /// let cjson = CJson::raw(c"[1, 2, 3]")?;
+    /// assert_eq!(cjson.as_raw(), Some(c"[1, 2, 3]"));
+    /// assert_eq!(CJson::null()?.as_raw(), None);
+    /// # Ok::<(), cjsonrs::Error>(())
+    /// ```
+    #[inline(always)]
+    pub fn as_raw(&self) -> Option<&CStr> {
+        if !self.is_raw() || self.0.valuestring.is_null() {
+            return None;
+        }
+
+        Some(unsafe { CStr::from_ptr(self.0.valuestring) })
+    }
+
     /// Returns the underlying [`cjsonrs_sys::cJSON`] object as a cstring, if
     /// possible.
     #[inline(always)]
@@ -157,6 +289,27 @@ impl<'json> CJsonRef<'json> {
         }
     }
 
+    /// Returns the underlying [`cjsonrs_sys::cJSON`] object as a UTF-8 string,
+    /// if possible.
+    ///
+    /// Returns [`None`] if the value is not a string, or an error if it is not
+    /// valid UTF-8.
+    ///
+    /// # Example usage
+    ///
+    /// ```
+    /// use cjsonrs::CJson;
+    ///
+    /// let cjson = CJson::from_str_value("héllo")?;
+    /// assert_eq!(cjson.as_str(), Some(Ok("héllo")));
+    /// assert_eq!(CJson::null()?.as_str(), None);
+    /// # Ok::<(), cjsonrs::Error>(())
+    /// ```
+    #[inline(always)]
+    pub fn as_str(&self) -> Option<Result<&str, Utf8Error>> {
+        self.as_c_string().map(CStr::to_str)
+    }
+
     /// Returns the underlying [`cjsonrs_sys::cJSON`] object as an array, if
     /// possible.
     #[inline(always)]
@@ -201,6 +354,144 @@ impl<'json> CJsonRef<'json> {
         }
     }
 
+    /// Sets the value of a null, boolean or number in place, turning it into a
+    /// number.
+    ///
+    /// Strings, arrays, objects and raw values own memory, so they cannot be
+    /// turned into numbers in place. Replace them instead, e.g. with
+    /// [`CJsonObject::insert`].
+    ///
+    /// # Example usage
+    ///
+    /// ```
+    /// use cjsonrs::CJson;
+    /// use cjsonrs::Error;
+    ///
+    /// let mut cjson: CJson = r#"{"reading": 20.5, "unit": "C"}"#.parse()?;
+    /// let mut object = cjson.as_mut_object().unwrap();
+    ///
+    /// object[c"reading"].set_number(21.0)?;
+    /// assert_eq!(object[c"unit"].set_number(1.0), Err(Error::TypeError));
+    /// assert_eq!(cjson.to_string(), r#"{"reading":21,"unit":"C"}"#);
+    /// # Ok::<(), Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function returns [`Error::TypeError`] if the value is not a null,
+    /// a boolean or a number.
+    #[inline(always)]
+    pub fn set_number(&mut self, n: impl Into<f64>) -> Result<(), Error> {
+        self.retag(cjsonrs_sys::cJSON_Number)?;
+        // Keeps `valueint` consistent with the double.
+        unsafe { cjsonrs_sys::cJSON_SetNumberHelper(self.as_mut_ptr(), n.into()) };
+        Ok(())
+    }
+
+    /// Sets the value of a null, boolean or number in place, turning it into a
+    /// boolean.
+    ///
+    /// See [`CJsonRef::set_number`] for more information.
+    ///
+    /// # Errors
+    ///
+    /// This function returns [`Error::TypeError`] if the value is not a null,
+    /// a boolean or a number.
+    #[inline(always)]
+    pub fn set_bool(&mut self, b: bool) -> Result<(), Error> {
+        let type_ = if b {
+            cjsonrs_sys::cJSON_True
+        } else {
+            cjsonrs_sys::cJSON_False
+        };
+        self.retag(type_)
+    }
+
+    /// Sets a null, boolean or number to null in place.
+    ///
+    /// See [`CJsonRef::set_number`] for more information.
+    ///
+    /// # Errors
+    ///
+    /// This function returns [`Error::TypeError`] if the value is not a null,
+    /// a boolean or a number.
+    #[inline(always)]
+    pub fn set_null(&mut self) -> Result<(), Error> {
+        self.retag(cjsonrs_sys::cJSON_NULL)
+    }
+
+    /// Changes the type of a null, boolean or number, clearing its value.
+    #[inline(always)]
+    fn retag(&mut self, type_: u32) -> Result<(), Error> {
+        if !(self.is_null() || self.is_bool() || self.is_number()) {
+            return Err(Error::TypeError);
+        }
+
+        self.0.type_ = type_ as core::ffi::c_int | (self.0.type_ & KEY_FLAGS);
+        self.0.valueint = 0;
+        self.0.valuedouble = 0.0;
+        Ok(())
+    }
+
+    /// Sets the value of a string in place.
+    ///
+    /// The string is copied. cJSON reuses the current buffer when the new
+    /// string fits in it, and strings that were created as references (e.g.
+    /// with [`CJson::string_reference`]) get a copy of their own, leaving the
+    /// referenced string untouched.
+    ///
+    /// # Example usage
+    ///
+    /// ```
+    /// use cjsonrs::CJson;
+    /// use cjsonrs::Error;
+    ///
+    /// let status = c"idle";
+    /// let mut cjson = CJson::string_reference(status)?;
+    ///
+    /// cjson.set_string(c"busy")?;
+    /// assert_eq!(cjson.as_c_string(), Some(c"busy"));
+    /// assert_eq!(status, c"idle");
+    /// assert_eq!(CJson::null()?.set_string(c"busy"), Err(Error::TypeError));
+    /// # Ok::<(), Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function returns [`Error::TypeError`] if the value is not a
+    /// string, or an error if the allocation fails.
+    pub fn set_string(&mut self, s: impl AsRef<CStr>) -> Result<(), Error> {
+        const REFERENCE: core::ffi::c_int = cjsonrs_sys::cJSON_IsReference as _;
+
+        if !self.is_string() {
+            return Err(Error::TypeError);
+        }
+        let s = s.as_ref();
+
+        let _usage = Usage::acquire();
+        if self.0.type_ & REFERENCE == 0 {
+            let result =
+                unsafe { cjsonrs_sys::cJSON_SetValuestring(self.as_mut_ptr(), s.as_ptr()) };
+            return if result.is_null() {
+                Err(Error::Allocation)
+            } else {
+                Ok(())
+            };
+        }
+
+        // cJSON refuses to modify borrowed strings, so the node takes
+        // ownership of a copy instead.
+        let bytes = s.to_bytes_with_nul();
+        let copy = unsafe { cjsonrs_sys::cJSON_malloc(bytes.len()) }.cast::<core::ffi::c_char>();
+        if copy.is_null() {
+            return Err(Error::Allocation);
+        }
+        unsafe { core::ptr::copy_nonoverlapping(bytes.as_ptr().cast(), copy, bytes.len()) };
+        self.0.valuestring = copy;
+        self.0.type_ &= !REFERENCE;
+        Ok(())
+    }
+
     /// Serializes the underlying [`cjsonrs_sys::cJSON`] object into a JSON
     /// string.
     #[inline(always)]
@@ -235,6 +526,113 @@ impl<'json> CJsonRef<'json> {
         }
     }
 
+    /// Serializes the underlying [`cjsonrs_sys::cJSON`] object into a JSON
+    /// string, starting with a buffer of `prebuffer` bytes.
+    ///
+    /// cJSON grows the buffer as needed, so a good guess of the final size
+    /// avoids repeated reallocations when printing large documents. See
+    /// [`CJsonRef::estimated_print_len`].
+    #[inline(always)]
+    pub fn to_c_string_with_capacity(
+        &self,
+        prebuffer: usize,
+        pretty: bool,
+    ) -> Result<CJsonString, Error> {
+        let ptr = self.as_ptr();
+        let prebuffer = prebuffer.clamp(1, core::ffi::c_int::MAX as usize);
+
+        let s = unsafe { cjsonrs_sys::cJSON_PrintBuffered(ptr, prebuffer as _, pretty as _) };
+
+        if let Some(ptr) = NonNull::new(s) {
+            let s = unsafe { CStr::from_ptr(ptr.as_ptr()) };
+            let len = s.to_bytes_with_nul().len();
+            unsafe { Ok(CJsonString::from_raw_parts(ptr, len)) }
+        } else {
+            Err(Error::Allocation)
+        }
+    }
+
+    /// Estimates the length of the JSON string produced by serializing the
+    /// underlying [`cjsonrs_sys::cJSON`] object, including the null
+    /// terminator.
+    ///
+    /// The estimate is exact, except for non-integer numbers, which are
+    /// assumed to take their longest representation. This makes it suitable
+    /// as the `prebuffer` of [`CJsonRef::to_c_string_with_capacity`].
+    pub fn estimated_print_len(&self, pretty: bool) -> usize {
+        self.estimated_print_len_at(pretty, 0) + 1
+    }
+
+    fn estimated_print_len_at(&self, pretty: bool, depth: usize) -> usize {
+        if let Some(b) = self.as_bool() {
+            if b {
+                4
+            } else {
+                5
+            }
+        } else if self.is_number() {
+            estimated_number_len(&self.0)
+        } else if let Some(s) = self.as_c_string() {
+            estimated_string_len(s)
+        } else if let Some(raw) = self.as_raw() {
+            raw.to_bytes().len()
+        } else if self.is_array() {
+            let separator = if pretty { 2 } else { 1 };
+            let items = self
+                .iter()
+                .map(|item| item.estimated_print_len_at(pretty, depth + 1) + separator)
+                .sum::<usize>();
+            // Brackets, minus the separator of the last item
+            (2 + items).saturating_sub(if self.is_empty() { 0 } else { separator })
+        } else if self.is_object() {
+            let depth = depth + 1;
+            let mut len = if pretty { 3 + depth - 1 } else { 2 };
+            for item in self.iter() {
+                len += item.name().map_or(2, estimated_string_len);
+                len += 2 + item.estimated_print_len_at(pretty, depth);
+                if pretty {
+                    len += depth + 2;
+                }
+            }
+            // No separator after the last member
+            len.saturating_sub(if self.is_empty() { 0 } else { 1 })
+        } else {
+            4
+        }
+    }
+
+    /// Serializes the underlying [`cjsonrs_sys::cJSON`] object into `buffer`
+    /// without allocating, returning the JSON string written to it.
+    ///
+    /// cJSON may require up to 5 more bytes than the final output while
+    /// printing numbers, so `buffer` should be slightly bigger than the
+    /// expected output.
+    ///
+    /// # Errors
+    ///
+    /// This function returns [`Error::BufferTooSmall`] if the output does not
+    /// fit in `buffer`, including the null terminator.
+    #[inline(always)]
+    pub fn print_into<'b>(&self, buffer: &'b mut [u8], pretty: bool) -> Result<&'b CStr, Error> {
+        // cJSON only reads the item, despite taking a mutable pointer.
+        let ptr = self.as_ptr() as *mut cjsonrs_sys::cJSON;
+        let len = buffer.len().min(core::ffi::c_int::MAX as usize);
+
+        let b = unsafe {
+            cjsonrs_sys::cJSON_PrintPreallocated(
+                ptr,
+                buffer.as_mut_ptr() as _,
+                len as _,
+                pretty as _,
+            )
+        };
+
+        if b == 0 {
+            return Err(Error::BufferTooSmall);
+        }
+        CStr::from_bytes_until_nul(buffer).map_err(|_| Error::BufferTooSmall)
+    }
+
     /// Returns either the number of key value pairs in the object or the number
     /// of items in the array. If the object is not an array or an object, this
     /// function returns 0.
@@ -281,6 +679,54 @@ impl<'json> CJsonRef<'json> {
         }
     }
 
+    /// Returns the integer counterpart of a number, as stored by cJSON.
+    #[inline(always)]
+    pub(super) fn valueint(&self) -> core::ffi::c_int {
+        self.0.valueint
+    }
+
+    /// Swaps the values of two nodes, leaving their keys and their positions
+    /// within their parents untouched.
+    #[inline(always)]
+    pub(super) fn swap_value(&mut self, other: &mut CJsonRef<'json>) {
+        let (a, b) = (&mut self.0, &mut other.0);
+        core::mem::swap(&mut a.child, &mut b.child);
+        core::mem::swap(&mut a.valuestring, &mut b.valuestring);
+        core::mem::swap(&mut a.valueint, &mut b.valueint);
+        core::mem::swap(&mut a.valuedouble, &mut b.valuedouble);
+
+        let (type_a, type_b) = (a.type_, b.type_);
+        a.type_ = (type_b & !KEY_FLAGS) | (type_a & KEY_FLAGS);
+        b.type_ = (type_a & !KEY_FLAGS) | (type_b & KEY_FLAGS);
+    }
+
+    /// Sorts the members of the object by key, in byte order or ignoring the
+    /// case of ASCII letters. If `recursive` is `true`, every nested object is
+    /// sorted as well, including the ones within arrays.
+    pub(super) fn sort_keys(&mut self, recursive: bool, case_sensitive: bool) {
+        if self.is_object() {
+            let ptr = self.as_mut_ptr();
+            unsafe {
+                if case_sensitive {
+                    cjsonrs_sys::cJSONUtils_SortObjectCaseSensitive(ptr)
+                } else {
+                    cjsonrs_sys::cJSONUtils_SortObject(ptr)
+                }
+            };
+        }
+        if !recursive {
+            return;
+        }
+
+        // Sorting relinks the children, so they are walked afterwards.
+        let mut child = self.0.child;
+        while !child.is_null() {
+            let cjson = unsafe { CJsonRef::from_mut_ptr(child) };
+            cjson.sort_keys(true, case_sensitive);
+            child = cjson.0.next;
+        }
+    }
+
     /// Duplicates the underlying [`cjsonrs_sys::cJSON`] object.
     #[inline(always)]
     pub fn duplicate(&self) -> Result<CJson<'json>, Error> {
@@ -321,6 +767,8 @@ impl Debug for CJsonRef<'_> {
             write!(f, "{a:?}")
         } else if let Some(o) = self.as_object() {
             write!(f, "{o:?}")
+        } else if let Some(r) = self.as_raw() {
+            write!(f, "Raw({r:?})")
         } else {
             write!(f, "Unknown({:x})", self.0.type_)
         }
@@ -329,21 +777,7 @@ impl Debug for CJsonRef<'_> {
 
 impl Display for CJsonRef<'_> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        let s = if f.alternate() {
-            self.to_c_string_pretty()
-        } else {
-            self.to_c_string()
-        };
-
-        let Ok(s) = s else {
-            return Err(core::fmt::Error);
-        };
-
-        let Ok(s) = s.to_str() else {
-            return Err(core::fmt::Error);
-        };
-
-        f.write_str(s)
+        self.to_fmt_writer(f, f.alternate())
     }
 }
 
@@ -370,7 +804,7 @@ impl<'json> AsMut<CJsonRef<'json>> for CJsonRef<'json> {
 
 impl<'a, 'json> IntoIterator for &'a CJsonRef<'json>
 where
-    'a: 'json,
+    'json: 'a,
 {
     type IntoIter = CJsonIter<'a, 'json>;
     type Item = &'a CJsonRef<'json>;
@@ -427,3 +861,33 @@ unsafe impl Send for CJsonIter<'_, '_> {}
 // SAFETY: See [crate level docs](::crate) for more information.
 #[cfg(feature = "sync")]
 unsafe impl Sync for CJsonIter<'_, '_> {}
+
+/// Estimates the length of a number as printed by cJSON.
+fn estimated_number_len(cjson: &cjsonrs_sys::cJSON) -> usize {
+    let n = cjson.valuedouble;
+    if !n.is_finite() {
+        // Printed as `null`
+        4
+    } else if n == cjson.valueint as f64 {
+        let digits = cjson.valueint.unsigned_abs().checked_ilog10().unwrap_or(0) + 1;
+        digits as usize + usize::from(cjson.valueint < 0)
+    } else {
+        // Longest `%1.17g` representation, e.g. `-2.2250738585072014e-308`
+        24
+    }
+}
+
+/// Estimates the length of a string as printed by cJSON, including quotes and
+/// escape sequences.
+fn estimated_string_len(s: &CStr) -> usize {
+    let escaped = s
+        .to_bytes()
+        .iter()
+        .map(|&b| match b {
+            b'"' | b'\\' | 0x08 | 0x0c | b'\n' | b'\r' | b'\t' => 2,
+            0..0x20 => 6,
+            _ => 1,
+        })
+        .sum::<usize>();
+    escaped + 2
+}
@@ -0,0 +1,91 @@
+use core::fmt::Write;
+use core::iter::FusedIterator;
+
+use super::CJson;
+use super::CJsonRef;
+use super::Error;
+use super::ParseError;
+use super::ParseOptions;
+
+/// An iterator over the JSON documents of a byte slice.
+///
+/// Documents may be concatenated or separated by whitespace, such as in
+/// newline-delimited JSON (NDJSON). Parsing stops after the first error, as
+/// there is no reliable way to find where the next document starts.
+///
+/// This iterator is created by [`CJson::iter_documents`].
+#[derive(Debug, Clone)]
+pub struct Documents<'a> {
+    input: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Documents<'a> {
+    /// Constructs a new [`Documents`] iterator over `input`.
+    #[inline(always)]
+    pub fn new(input: &'a [u8]) -> Self {
+        Self { input, offset: 0 }
+    }
+
+    /// Returns the part of the input that has not been parsed yet.
+    #[inline(always)]
+    pub fn remainder(&self) -> &'a [u8] {
+        &self.input[self.offset..]
+    }
+}
+
+impl Iterator for Documents<'_> {
+    type Item = Result<CJson<'static>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let remainder = self.remainder();
+        let start = remainder.iter().position(|&b| b > b' ')?;
+        let document = &remainder[start..];
+
+        match ParseOptions::new().parse(document) {
+            Ok((cjson, rest)) => {
+                self.offset = self.input.len() - rest.len();
+                Some(Ok(cjson))
+            }
+            Err(Error::Parse(error)) => {
+                let offset = self.offset + start + error.offset();
+                self.offset = self.input.len();
+                Some(Err(ParseError::from_offset(self.input, offset).into()))
+            }
+            Err(error) => {
+                self.offset = self.input.len();
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+impl FusedIterator for Documents<'_> {}
+
+/// Writes each value as compact JSON followed by a newline, producing
+/// [JSON Lines](https://jsonlines.org/).
+///
+/// # Example usage
+///
+/// ```
+/// use cjsonrs::cjson;
+/// use cjsonrs::write_json_lines;
+///
+/// let values = [cjson!(1).unwrap(), cjson!(c"two").unwrap()];
+/// let mut output = String::new();
+/// write_json_lines(&mut output, &values).unwrap();
+///
+/// assert_eq!(output, "1\n\"two\"\n");
+/// ```
+pub fn write_json_lines<'json, W, I>(writer: &mut W, values: I) -> core::fmt::Result
+where
+    W: Write + ?Sized,
+    I: IntoIterator,
+    I::Item: AsRef<CJsonRef<'json>>,
+{
+    for value in values {
+        write!(writer, "{}", value.as_ref())?;
+        writer.write_char('\n')?;
+    }
+    Ok(())
+}
@@ -1,4 +1,5 @@
 use core::convert::Infallible;
+use core::fmt::Display;
 
 /// An error type for [`CJson`]
 ///
@@ -6,14 +7,31 @@ use core::convert::Infallible;
 #[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Error {
     /// Error variant for failing string parsing due to malformed JSON
-    #[error("Failed to parse JSON string")]
-    Parse,
+    #[error("Failed to parse JSON string: {0}")]
+    Parse(ParseError),
     /// Error variant for allocation errors
     #[error("Failed to allocate enough memory for CJson")]
     Allocation,
     /// Error variant for type errors.
     #[error("Type error occurred when converting CJson to a different type")]
     TypeError,
+    /// Error variant for printing into a buffer that cannot hold the output
+    #[error("Buffer is too small to hold the serialized JSON")]
+    BufferTooSmall,
+    /// Error variant for failing to apply a JSON patch
+    #[error("Failed to apply JSON patch: {0}")]
+    Patch(PatchError),
+    /// Error variant for numbers that cJSON cannot represent exactly
+    #[error("Number cannot be represented exactly as a double")]
+    InexactNumber,
+    /// Error variant for strings that cannot be converted into C strings, as
+    /// they contain a nul byte at the given position
+    #[error("String contains a nul byte at position {0}")]
+    InteriorNul(usize),
+    /// Error variant for operations that require [hooks](super::hooks) to be
+    /// installed, such as limits and arenas
+    #[error("Operation requires cJSON hooks to be installed")]
+    HooksRequired,
 }
 
 impl From<Infallible> for Error {
@@ -21,3 +39,109 @@ impl From<Infallible> for Error {
         unreachable!("Infallible type should never be constructed")
     }
 }
+
+impl From<ParseError> for Error {
+    fn from(value: ParseError) -> Self {
+        Error::Parse(value)
+    }
+}
+
+impl From<PatchError> for Error {
+    fn from(value: PatchError) -> Self {
+        Error::Patch(value)
+    }
+}
+
+/// The location where cJSON stopped parsing a malformed JSON document.
+///
+/// Lines and columns are 1-based and columns are counted in bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ParseError {
+    offset: usize,
+    line: usize,
+    column: usize,
+}
+
+impl ParseError {
+    /// Constructs a [`ParseError`] pointing at the given byte offset of `input`.
+    pub(crate) fn from_offset(input: &[u8], offset: usize) -> Self {
+        let offset = offset.min(input.len());
+        let consumed = &input[..offset];
+        let line = consumed.iter().filter(|&&b| b == b'\n').count() + 1;
+        let column = match consumed.iter().rposition(|&b| b == b'\n') {
+            Some(newline) => offset - newline,
+            None => offset + 1,
+        };
+
+        Self {
+            offset,
+            line,
+            column,
+        }
+    }
+
+    /// Returns the byte offset where parsing failed.
+    #[inline(always)]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the line where parsing failed.
+    #[inline(always)]
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Returns the column where parsing failed.
+    #[inline(always)]
+    pub fn column(&self) -> usize {
+        self.column
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "unexpected input at line {}, column {} (byte {})",
+            self.line, self.column, self.offset
+        )
+    }
+}
+
+/// An error produced while applying a JSON Patch.
+///
+/// cJSON stops at the first failing operation, so earlier operations of the
+/// same patch remain applied.
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PatchError {
+    /// The patch is not an array of well-formed operations, such as an
+    /// operation without `op` or `path`, or with an unknown `op`.
+    #[error("malformed patch")]
+    Malformed,
+    /// A `test` operation did not match the target value.
+    #[error("test operation failed")]
+    TestFailed,
+    /// A `path` or `from` pointer does not refer to an existing location.
+    #[error("path does not exist")]
+    MissingPath,
+    /// An `add`, `replace` or `remove` operation targets the root value itself
+    /// (an empty `path`), which cJSON can only do by overwriting it in place.
+    #[error("operations on the root value are not supported")]
+    RootOperation,
+    /// cJSON ran out of memory while applying the patch.
+    #[error("failed to allocate memory")]
+    Allocation,
+}
+
+/// An error produced while installing [hooks](super::hooks).
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HooksError {
+    /// A value allocated by cJSON is still alive, and would be freed with the
+    /// wrong allocator.
+    #[error("values allocated by cJSON are still alive")]
+    InUse,
+    /// Hooks can only be installed once.
+    #[error("hooks are already installed")]
+    AlreadyInstalled,
+}
@@ -0,0 +1,621 @@
+//! Safe installation of custom allocators for cJSON.
+//!
+//! cJSON allocates through a pair of global `malloc`/`free` hooks, which
+//! default to the C standard library. Replacing them with
+//! [`cjsonrs_sys::cJSON_InitHooks`] while values are alive makes cJSON free
+//! memory with an allocator that did not allocate it. [`install`] prevents
+//! this by tracking every owned [`CJson`](super::CJson) and
+//! [`CJsonString`](super::CJsonString) value, and by allowing hooks to be
+//! installed only once.
+//!
+//! With the `rust-alloc` feature, cJSON allocates through Rust's global
+//! allocator by default, see [`RustAlloc`]. Hooks installed before the first
+//! value is created still take precedence. The vendored cJSON is then built
+//! without references to `malloc`, `free` and `realloc`, so no C heap needs to
+//! be linked. The feature requires pointer-sized atomics.
+//!
+//! With `std`, installed hooks also allow limiting how much memory parsing
+//! may take, see `CJson::from_slice_with_limit`, and parsing into arenas, see
+//! `Arena`. [`Counting`] keeps track of the memory cJSON is using.
+//!
+//! # Example usage
+//!
+//! ```no_run
+//! use core::ffi::c_void;
+//!
+//! use cjsonrs::hooks::Allocator;
+//!
+//! extern "C" {
+//!     fn malloc(size: usize) -> *mut c_void;
+//!     fn free(ptr: *mut c_void);
+//! }
+//!
+//! struct Libc;
+//!
+//! unsafe impl Allocator for Libc {
+//!     fn malloc(&self, size: usize) -> *mut c_void {
+//!         unsafe { malloc(size) }
+//!     }
+//!
+//!     unsafe fn free(&self, ptr: *mut c_void) {
+//!         free(ptr)
+//!     }
+//! }
+//!
+//! static LIBC: Libc = Libc;
+//!
+//! fn main() {
+//!     cjsonrs::hooks::install(&LIBC).expect("no cJSON values are alive yet");
+//! }
+//! ```
+
+#[cfg(all(feature = "rust-alloc", not(target_has_atomic = "ptr")))]
+compile_error!("the `rust-alloc` feature requires a target with pointer-sized atomics");
+
+cfg_if::cfg_if! {
+    if #[cfg(all(feature = "rust-alloc", feature = "std"))] {
+        use std::alloc::alloc;
+        use std::alloc::dealloc;
+    } else if #[cfg(feature = "rust-alloc")] {
+        extern crate alloc;
+        use alloc::alloc::alloc;
+        use alloc::alloc::dealloc;
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+use core::alloc::Layout;
+#[cfg(target_has_atomic = "ptr")]
+use core::ffi::c_void;
+#[cfg(target_has_atomic = "ptr")]
+use core::sync::atomic::AtomicPtr;
+#[cfg(target_has_atomic = "ptr")]
+use core::sync::atomic::AtomicUsize;
+#[cfg(target_has_atomic = "ptr")]
+use core::sync::atomic::Ordering;
+
+#[cfg(all(feature = "std", target_has_atomic = "ptr"))]
+use super::Error;
+#[cfg(target_has_atomic = "ptr")]
+use super::HooksError;
+
+/// An allocator that cJSON can use for every allocation.
+///
+/// Allocators are called from whichever thread uses cJSON, so they must be
+/// [`Sync`]. Panicking within an allocator aborts the process, as the panic
+/// cannot unwind through cJSON.
+///
+/// # Safety
+///
+/// Implementations must uphold the same contract as C's `malloc` and `free`:
+///
+/// - [`Allocator::malloc`] must return either a null pointer or a pointer to
+///   `size` bytes, suitably aligned for any C type, that are not in use
+///   anywhere else.
+/// - Memory must remain valid until it is passed to [`Allocator::free`].
+#[cfg(target_has_atomic = "ptr")]
+pub unsafe trait Allocator: Sync {
+    /// Allocates `size` bytes, returning a null pointer on failure.
+    fn malloc(&self, size: usize) -> *mut c_void;
+
+    /// Frees memory allocated by [`Allocator::malloc`].
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been returned by [`Allocator::malloc`] on this same
+    /// allocator and must not have been freed already.
+    unsafe fn free(&self, ptr: *mut c_void);
+}
+
+/// Installs `allocator` as cJSON's allocator.
+///
+/// Hooks can only be installed once, before any [`CJson`](super::CJson) or
+/// [`CJsonString`](super::CJsonString) value is alive. Values created from
+/// other threads while the hooks are being installed wait until the
+/// installation finishes.
+///
+/// # Errors
+///
+/// This function returns [`HooksError::InUse`] if any value allocated by
+/// cJSON is still alive, or [`HooksError::AlreadyInstalled`] if hooks were
+/// installed before.
+#[cfg(target_has_atomic = "ptr")]
+pub fn install<A: Allocator>(allocator: &'static A) -> Result<(), HooksError> {
+    if let Err(state) = STATE.compare_exchange(0, INSTALLING, Ordering::Acquire, Ordering::Relaxed)
+    {
+        return Err(if state & (INSTALLING | INSTALLED) != 0 {
+            HooksError::AlreadyInstalled
+        } else {
+            HooksError::InUse
+        });
+    }
+
+    ALLOCATOR.store(allocator as *const A as *mut (), Ordering::Release);
+    let mut hooks = cjsonrs_sys::cJSON_Hooks {
+        malloc_fn: Some(malloc_fn::<A>),
+        free_fn: Some(free_fn::<A>),
+    };
+    // No value is alive and no other thread can create one until the state
+    // is released, so cJSON cannot be in use.
+    unsafe { cjsonrs_sys::cJSON_InitHooks(&mut hooks) };
+
+    STATE.store(INSTALLED, Ordering::Release);
+    Ok(())
+}
+
+/// An [`Allocator`] forwarding to Rust's global allocator.
+///
+/// Each allocation is prefixed with a header holding its size, which Rust
+/// needs to free it but cJSON does not pass to `free`. This allows using
+/// cJSON on targets without a C heap, keeping all memory accounting within
+/// the `#[global_allocator]`.
+///
+/// The `rust-alloc` feature installs this allocator when the first value is
+/// created, unless other hooks were installed before.
+#[cfg(all(feature = "rust-alloc", target_has_atomic = "ptr"))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct RustAlloc;
+
+#[cfg(all(feature = "rust-alloc", target_has_atomic = "ptr"))]
+unsafe impl Allocator for RustAlloc {
+    fn malloc(&self, size: usize) -> *mut c_void {
+        let Some(layout) = layout_with_header(size) else {
+            return core::ptr::null_mut();
+        };
+
+        let ptr = unsafe { alloc(layout) };
+        if ptr.is_null() {
+            return core::ptr::null_mut();
+        }
+        unsafe {
+            ptr.cast::<usize>().write(size);
+            ptr.add(HEADER).cast()
+        }
+    }
+
+    unsafe fn free(&self, ptr: *mut c_void) {
+        // Like C's `free`, null pointers are ignored.
+        if ptr.is_null() {
+            return;
+        }
+
+        unsafe {
+            let ptr = ptr.cast::<u8>().sub(HEADER);
+            let size = ptr.cast::<usize>().read();
+            // The layout was valid when the memory was allocated.
+            let layout = layout_with_header(size).unwrap_unchecked();
+            dealloc(ptr, layout);
+        }
+    }
+}
+
+/// An [`Allocator`] that keeps statistics about the memory allocated through
+/// another allocator.
+///
+/// Like [`RustAlloc`], each allocation is prefixed with a header holding its
+/// size, so that frees can be accounted for.
+///
+/// # Example usage
+///
+/// ```
+/// use cjsonrs::hooks::Counting;
+/// # use cjsonrs::hooks::Allocator;
+/// # struct Libc;
+/// # unsafe impl Allocator for Libc {
+/// #     fn malloc(&self, _: usize) -> *mut core::ffi::c_void { core::ptr::null_mut() }
+/// #     unsafe fn free(&self, _: *mut core::ffi::c_void) {}
+/// # }
+///
+/// static COUNTING: Counting<Libc> = Counting::new(Libc);
+///
+/// let stats = COUNTING.stats();
+/// assert_eq!(stats.live_bytes(), 0);
+/// assert_eq!(stats.allocations(), 0);
+/// ```
+#[cfg(target_has_atomic = "ptr")]
+#[derive(Debug, Default)]
+pub struct Counting<A> {
+    inner: A,
+    live_bytes: AtomicUsize,
+    peak_bytes: AtomicUsize,
+    allocations: AtomicUsize,
+    frees: AtomicUsize,
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<A> Counting<A> {
+    /// Wraps `inner`, starting with empty statistics.
+    #[inline(always)]
+    pub const fn new(inner: A) -> Self {
+        Self {
+            inner,
+            live_bytes: AtomicUsize::new(0),
+            peak_bytes: AtomicUsize::new(0),
+            allocations: AtomicUsize::new(0),
+            frees: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the wrapped allocator.
+    #[inline(always)]
+    pub fn inner(&self) -> &A {
+        &self.inner
+    }
+
+    /// Returns a snapshot of the statistics. Sizes do not include the
+    /// headers of each allocation.
+    #[inline(always)]
+    pub fn stats(&self) -> Stats {
+        Stats {
+            live_bytes: self.live_bytes.load(Ordering::Relaxed),
+            peak_bytes: self.peak_bytes.load(Ordering::Relaxed),
+            allocations: self.allocations.load(Ordering::Relaxed),
+            frees: self.frees.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+unsafe impl<A: Allocator> Allocator for Counting<A> {
+    fn malloc(&self, size: usize) -> *mut c_void {
+        let Some(layout) = layout_with_header(size) else {
+            return core::ptr::null_mut();
+        };
+
+        let ptr = self.inner.malloc(layout.size()).cast::<u8>();
+        if ptr.is_null() {
+            return core::ptr::null_mut();
+        }
+
+        let live_bytes = self.live_bytes.fetch_add(size, Ordering::Relaxed) + size;
+        self.peak_bytes.fetch_max(live_bytes, Ordering::Relaxed);
+        self.allocations.fetch_add(1, Ordering::Relaxed);
+        unsafe {
+            ptr.cast::<usize>().write(size);
+            ptr.add(HEADER).cast()
+        }
+    }
+
+    unsafe fn free(&self, ptr: *mut c_void) {
+        if ptr.is_null() {
+            return;
+        }
+
+        unsafe {
+            let ptr = ptr.cast::<u8>().sub(HEADER);
+            let size = ptr.cast::<usize>().read();
+            self.live_bytes.fetch_sub(size, Ordering::Relaxed);
+            self.frees.fetch_add(1, Ordering::Relaxed);
+            self.inner.free(ptr.cast());
+        }
+    }
+}
+
+/// A snapshot of the statistics kept by [`Counting`].
+#[cfg(target_has_atomic = "ptr")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Stats {
+    live_bytes: usize,
+    peak_bytes: usize,
+    allocations: usize,
+    frees: usize,
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl Stats {
+    /// Returns the number of bytes currently allocated.
+    #[inline(always)]
+    pub fn live_bytes(&self) -> usize {
+        self.live_bytes
+    }
+
+    /// Returns the highest number of bytes allocated at the same time.
+    #[inline(always)]
+    pub fn peak_bytes(&self) -> usize {
+        self.peak_bytes
+    }
+
+    /// Returns the number of successful allocations.
+    #[inline(always)]
+    pub fn allocations(&self) -> usize {
+        self.allocations
+    }
+
+    /// Returns the number of frees.
+    #[inline(always)]
+    pub fn frees(&self) -> usize {
+        self.frees
+    }
+}
+
+/// Runs `f` allowing cJSON to allocate at most `limit` bytes in total from
+/// the current thread.
+///
+/// Limits are enforced by the installed hooks. If none are installed, cJSON
+/// allocates with C's `malloc`, which cannot be limited, so
+/// [`Error::HooksRequired`] is returned without running `f`. If the limit is
+/// exceeded, [`Error::Allocation`] is returned.
+#[cfg(all(feature = "std", target_has_atomic = "ptr"))]
+pub(super) fn with_limit<R>(limit: usize, f: impl FnOnce() -> R) -> Result<R, Error> {
+    if STATE.load(Ordering::Acquire) & INSTALLED == 0 {
+        return Err(Error::HooksRequired);
+    }
+
+    let budget = Budget {
+        remaining: limit,
+        exceeded: false,
+    };
+    let previous = budget::replace(Some(budget));
+    let result = f();
+    let budget = budget::replace(previous);
+
+    match budget {
+        Some(budget) if budget.exceeded => Err(Error::Allocation),
+        _ => Ok(result),
+    }
+}
+
+/// Runs `f` making cJSON allocate from `buffer` on the current thread.
+///
+/// Allocations bump a pointer within `buffer`, while frees of memory within
+/// `buffer` are ignored. Like limits, arenas are handled by the installed
+/// hooks, so [`Error::HooksRequired`] is returned without running `f` if none
+/// are installed. If `buffer` is exhausted, [`Error::Allocation`] is returned.
+#[cfg(all(feature = "std", target_has_atomic = "ptr"))]
+pub(super) fn with_arena<R>(buffer: &mut [u8], f: impl FnOnce() -> R) -> Result<R, Error> {
+    if STATE.load(Ordering::Acquire) & INSTALLED == 0 {
+        return Err(Error::HooksRequired);
+    }
+
+    let region = Region {
+        start: buffer.as_mut_ptr(),
+        len: buffer.len(),
+        used: 0,
+        exhausted: false,
+    };
+    let previous = arena::replace(Some(region));
+    let result = f();
+    let region = arena::replace(previous);
+
+    match region {
+        Some(region) if region.exhausted => Err(Error::Allocation),
+        _ => Ok(result),
+    }
+}
+
+/// Marks cJSON as in use for as long as it is alive, so that hooks are never
+/// replaced while cJSON memory is in use.
+///
+/// Owned values keep one for their whole lifetime, see [`Usage::forget`]. It
+/// must also be acquired before calling into cJSON whenever no other value
+/// keeps cJSON in use.
+pub(super) struct Usage(());
+
+impl Usage {
+    /// Marks cJSON as in use, waiting for any ongoing installation.
+    #[inline(always)]
+    pub(super) fn acquire() -> Self {
+        #[cfg(target_has_atomic = "ptr")]
+        {
+            let mut state = STATE.load(Ordering::Relaxed);
+            loop {
+                #[cfg(feature = "rust-alloc")]
+                if state == 0 {
+                    // Nothing was ever installed, so the default allocator is.
+                    let _ = install(&RustAlloc);
+                    state = STATE.load(Ordering::Relaxed);
+                    continue;
+                }
+                if state & INSTALLING != 0 {
+                    core::hint::spin_loop();
+                    state = STATE.load(Ordering::Relaxed);
+                    continue;
+                }
+                match STATE.compare_exchange_weak(
+                    state,
+                    state + 1,
+                    Ordering::Acquire,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => break,
+                    Err(current) => state = current,
+                }
+            }
+        }
+
+        Self(())
+    }
+
+    /// Keeps cJSON marked as in use without a [`Usage`] value, until
+    /// [`Usage::release`] is called.
+    #[inline(always)]
+    pub(super) fn forget(self) {
+        core::mem::forget(self);
+    }
+
+    /// Releases a usage previously kept with [`Usage::forget`].
+    ///
+    /// # Safety
+    ///
+    /// Every call must be paired with an earlier call to [`Usage::forget`].
+    #[inline(always)]
+    pub(super) unsafe fn release() {
+        #[cfg(target_has_atomic = "ptr")]
+        STATE.fetch_sub(1, Ordering::Release);
+    }
+}
+
+impl Drop for Usage {
+    #[inline(always)]
+    fn drop(&mut self) {
+        unsafe { Self::release() };
+    }
+}
+
+/// The size of the header prefixed to allocations by [`RustAlloc`] and
+/// [`Counting`], which also is the alignment of every allocation. It matches
+/// the alignment of `max_align_t` on common targets, as required from
+/// `malloc`.
+#[cfg(target_has_atomic = "ptr")]
+const HEADER: usize = 16;
+
+/// Returns the layout of an allocation of `size` bytes, header included.
+#[cfg(target_has_atomic = "ptr")]
+#[inline(always)]
+fn layout_with_header(size: usize) -> Option<Layout> {
+    let size = size.checked_add(HEADER)?;
+    Layout::from_size_align(size, HEADER).ok()
+}
+
+/// The bytes cJSON may still allocate within [`with_limit`].
+#[cfg(all(feature = "std", target_has_atomic = "ptr"))]
+#[derive(Debug, Clone, Copy)]
+struct Budget {
+    remaining: usize,
+    exceeded: bool,
+}
+
+/// Per-thread storage of the active [`Budget`].
+#[cfg(all(feature = "std", target_has_atomic = "ptr"))]
+mod budget {
+    use core::cell::Cell;
+
+    use super::Budget;
+
+    std::thread_local! {
+        static BUDGET: Cell<Option<Budget>> = const { Cell::new(None) };
+    }
+
+    /// Replaces the budget of the current thread, returning the previous one.
+    pub(super) fn replace(budget: Option<Budget>) -> Option<Budget> {
+        BUDGET.with(|cell| cell.replace(budget))
+    }
+
+    /// Takes `size` bytes from the budget of the current thread, if any,
+    /// returning `false` if there are not enough left.
+    pub(super) fn charge(size: usize) -> bool {
+        BUDGET
+            .try_with(|cell| {
+                let Some(mut budget) = cell.get() else {
+                    return true;
+                };
+                match budget.remaining.checked_sub(size) {
+                    Some(remaining) => budget.remaining = remaining,
+                    None => budget.exceeded = true,
+                }
+                cell.set(Some(budget));
+                !budget.exceeded
+            })
+            .unwrap_or(true)
+    }
+}
+
+/// The memory cJSON allocates from within [`with_arena`].
+#[cfg(all(feature = "std", target_has_atomic = "ptr"))]
+#[derive(Debug, Clone, Copy)]
+struct Region {
+    start: *mut u8,
+    len: usize,
+    used: usize,
+    exhausted: bool,
+}
+
+/// Per-thread storage of the active [`Region`].
+///
+/// Arenas are only available with `std`, as allocations from other threads
+/// must never end up in them.
+#[cfg(all(feature = "std", target_has_atomic = "ptr"))]
+mod arena {
+    use core::cell::Cell;
+    use core::ffi::c_void;
+
+    use super::Region;
+    use super::HEADER;
+
+    std::thread_local! {
+        static REGION: Cell<Option<Region>> = const { Cell::new(None) };
+    }
+
+    /// Replaces the region of the current thread, returning the previous one.
+    pub(super) fn replace(region: Option<Region>) -> Option<Region> {
+        REGION.with(|cell| cell.replace(region))
+    }
+
+    /// Allocates `size` bytes from the region of the current thread, or
+    /// returns [`None`] if there is no region.
+    pub(super) fn allocate(size: usize) -> Option<*mut c_void> {
+        REGION
+            .try_with(|cell| {
+                let mut region = cell.get()?;
+                // Allocations are aligned like the ones of `malloc`.
+                let offset = region.start.align_offset(HEADER);
+                let ptr = offset
+                    .checked_add(region.used.next_multiple_of(HEADER))
+                    .filter(|&start| start <= region.len && region.len - start >= size)
+                    .map(|start| {
+                        region.used = start - offset + size;
+                        unsafe { region.start.add(start).cast() }
+                    });
+
+                region.exhausted |= ptr.is_none();
+                cell.set(Some(region));
+                Some(ptr.unwrap_or(core::ptr::null_mut()))
+            })
+            .ok()
+            .flatten()
+    }
+
+    /// Returns `true` if `ptr` was allocated from the region of the current
+    /// thread.
+    pub(super) fn contains(ptr: *mut c_void) -> bool {
+        REGION
+            .try_with(|cell| {
+                cell.get().is_some_and(|region| {
+                    let start = region.start as usize;
+                    (start..start + region.len).contains(&(ptr as usize))
+                })
+            })
+            .unwrap_or(false)
+    }
+}
+
+/// Set while [`install`] replaces the hooks.
+#[cfg(target_has_atomic = "ptr")]
+const INSTALLING: usize = 1 << (usize::BITS - 1);
+/// Set once [`install`] has replaced the hooks.
+#[cfg(target_has_atomic = "ptr")]
+const INSTALLED: usize = 1 << (usize::BITS - 2);
+
+/// The installation flags, alongside the number of [`Usage`] values alive.
+#[cfg(target_has_atomic = "ptr")]
+static STATE: AtomicUsize = AtomicUsize::new(0);
+/// The installed allocator, whose type is only known by the hooks.
+#[cfg(target_has_atomic = "ptr")]
+static ALLOCATOR: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
+
+#[cfg(target_has_atomic = "ptr")]
+unsafe extern "C" fn malloc_fn<A: Allocator>(size: usize) -> *mut c_void {
+    #[cfg(feature = "std")]
+    if !budget::charge(size) {
+        return core::ptr::null_mut();
+    }
+    #[cfg(feature = "std")]
+    if let Some(ptr) = arena::allocate(size) {
+        return ptr;
+    }
+
+    let allocator = unsafe { &*ALLOCATOR.load(Ordering::Acquire).cast::<A>() };
+    allocator.malloc(size)
+}
+
+#[cfg(target_has_atomic = "ptr")]
+unsafe extern "C" fn free_fn<A: Allocator>(ptr: *mut c_void) {
+    // Arena memory is released all at once, when the arena is done with.
+    #[cfg(feature = "std")]
+    if arena::contains(ptr) {
+        return;
+    }
+
+    let allocator = unsafe { &*ALLOCATOR.load(Ordering::Acquire).cast::<A>() };
+    unsafe { allocator.free(ptr) }
+}
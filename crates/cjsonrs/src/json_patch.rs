@@ -0,0 +1,286 @@
+use core::ffi::CStr;
+use core::fmt::Display;
+
+use super::patch::member;
+use super::pointer::is_valid_pointer;
+use super::CJson;
+use super::CJsonArray;
+use super::CJsonObject;
+use super::CJsonRef;
+use super::Error;
+use super::PatchError;
+
+/// A single [JSON Patch](https://www.rfc-editor.org/rfc/rfc6902) operation.
+///
+/// Paths are JSON Pointers, see [`CJsonRef::pointer`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Op<'a, 'json> {
+    /// Adds `value` at `path`, replacing any existing object member.
+    Add {
+        path: &'a CStr,
+        value: &'a CJsonRef<'json>,
+    },
+    /// Removes the value at `path`.
+    Remove { path: &'a CStr },
+    /// Replaces the value at `path`, which must exist.
+    Replace {
+        path: &'a CStr,
+        value: &'a CJsonRef<'json>,
+    },
+    /// Moves the value at `from` to `path`.
+    Move { from: &'a CStr, path: &'a CStr },
+    /// Copies the value at `from` to `path`.
+    Copy { from: &'a CStr, path: &'a CStr },
+    /// Checks that the value at `path` is equal to `value`.
+    Test {
+        path: &'a CStr,
+        value: &'a CJsonRef<'json>,
+    },
+}
+
+impl<'a, 'json> Op<'a, 'json> {
+    /// Returns the target location of the operation.
+    #[inline(always)]
+    pub fn path(&self) -> &'a CStr {
+        match *self {
+            Op::Add { path, .. }
+            | Op::Remove { path }
+            | Op::Replace { path, .. }
+            | Op::Move { path, .. }
+            | Op::Copy { path, .. }
+            | Op::Test { path, .. } => path,
+        }
+    }
+
+    /// Returns the source location of `move` and `copy` operations.
+    #[inline(always)]
+    pub fn from(&self) -> Option<&'a CStr> {
+        match *self {
+            Op::Move { from, .. } | Op::Copy { from, .. } => Some(from),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of `add`, `replace` and `test` operations.
+    #[inline(always)]
+    pub fn value(&self) -> Option<&'a CJsonRef<'json>> {
+        match *self {
+            Op::Add { value, .. } | Op::Replace { value, .. } | Op::Test { value, .. } => {
+                Some(value)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the name of the operation, as written in the `op` member.
+    #[inline(always)]
+    pub fn name(&self) -> &'static CStr {
+        match self {
+            Op::Add { .. } => c"add",
+            Op::Remove { .. } => c"remove",
+            Op::Replace { .. } => c"replace",
+            Op::Move { .. } => c"move",
+            Op::Copy { .. } => c"copy",
+            Op::Test { .. } => c"test",
+        }
+    }
+}
+
+impl<'a, 'json> TryFrom<&'a CJsonRef<'json>> for Op<'a, 'json> {
+    type Error = PatchError;
+
+    fn try_from(operation: &'a CJsonRef<'json>) -> Result<Self, Self::Error> {
+        Op::from_operation(operation, true)
+    }
+}
+
+impl<'a, 'json> Op<'a, 'json> {
+    /// Decodes an operation, looking its members up the same way cJSON does
+    /// when applying it.
+    pub(super) fn from_operation(
+        operation: &'a CJsonRef<'json>,
+        case_sensitive: bool,
+    ) -> Result<Self, PatchError> {
+        if !operation.is_object() {
+            return Err(PatchError::Malformed);
+        }
+
+        let pointer = |key: &CStr| {
+            member(operation, key, case_sensitive)
+                .and_then(CJsonRef::as_c_string)
+                .filter(|pointer| is_valid_pointer(pointer))
+                .ok_or(PatchError::Malformed)
+        };
+        let value = || member(operation, c"value", case_sensitive).ok_or(PatchError::Malformed);
+        let op = member(operation, c"op", case_sensitive)
+            .and_then(CJsonRef::as_c_string)
+            .ok_or(PatchError::Malformed)?;
+        let path = pointer(c"path")?;
+
+        match op.to_bytes() {
+            b"add" => Ok(Op::Add {
+                path,
+                value: value()?,
+            }),
+            b"remove" => Ok(Op::Remove { path }),
+            b"replace" => Ok(Op::Replace {
+                path,
+                value: value()?,
+            }),
+            b"move" => Ok(Op::Move {
+                from: pointer(c"from")?,
+                path,
+            }),
+            b"copy" => Ok(Op::Copy {
+                from: pointer(c"from")?,
+                path,
+            }),
+            b"test" => Ok(Op::Test {
+                path,
+                value: value()?,
+            }),
+            _ => Err(PatchError::Malformed),
+        }
+    }
+}
+
+/// A validated [JSON Patch](https://www.rfc-editor.org/rfc/rfc6902) document.
+///
+/// Operations are stored as a cJSON array, so patches can be converted from
+/// and into [`CJsonArray`] values without copying.
+///
+/// # Example usage
+///
+/// ```
+/// use cjsonrs::CJson;
+/// use cjsonrs::JsonPatch;
+/// use cjsonrs::Op;
+///
+/// let mut device: CJson = r#"{"state": "off", "errors": [1]}"#.parse().unwrap();
+/// let on = CJson::string(c"on").unwrap();
+///
+/// let mut patch = JsonPatch::new().unwrap();
+/// patch.push(Op::Test { path: c"/state", value: &CJson::string(c"off").unwrap() }).unwrap();
+/// patch.push(Op::Replace { path: c"/state", value: &on }).unwrap();
+/// patch.push(Op::Remove { path: c"/errors/0" }).unwrap();
+///
+/// patch.apply(&mut device).unwrap();
+/// assert_eq!(device.to_string(), r#"{"errors":[],"state":"on"}"#);
+///
+/// // The `test` operation fails now, so the document is left untouched.
+/// assert!(patch.apply(&mut device).is_err());
+/// assert_eq!(device.to_string(), r#"{"errors":[],"state":"on"}"#);
+/// ```
+#[derive(Debug, PartialEq)]
+pub struct JsonPatch<'json> {
+    operations: CJsonArray<CJson<'json>>,
+}
+
+impl<'json> JsonPatch<'json> {
+    /// Creates a new empty [`JsonPatch`].
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the underlying array cannot be
+    /// allocated.
+    #[inline(always)]
+    pub fn new() -> Result<Self, Error> {
+        Ok(Self {
+            operations: CJsonArray::new()?,
+        })
+    }
+
+    /// Appends an operation to the patch. Values are duplicated.
+    ///
+    /// # Errors
+    ///
+    /// This function returns [`PatchError::Malformed`] if a path is not a
+    /// valid JSON Pointer, or an error if allocation fails.
+    pub fn push(&mut self, op: Op<'_, 'json>) -> Result<(), Error> {
+        let from_is_valid = op.from().is_none_or(is_valid_pointer);
+        if !is_valid_pointer(op.path()) || !from_is_valid {
+            return Err(PatchError::Malformed.into());
+        }
+
+        let mut operation = CJsonObject::new()?;
+        operation.insert(c"op", CJson::string_reference(op.name())?);
+        operation.insert(c"path", CJson::string(op.path())?);
+        if let Some(from) = op.from() {
+            operation.insert(c"from", CJson::string(from)?);
+        }
+        if let Some(value) = op.value() {
+            operation.insert(c"value", value.duplicate()?);
+        }
+
+        self.operations.push(operation);
+        Ok(())
+    }
+
+    /// Returns an iterator over the operations of the patch.
+    #[inline(always)]
+    pub fn iter(&self) -> impl Iterator<Item = Op<'_, 'json>> + '_ {
+        self.operations
+            .iter()
+            .map(|operation| Op::try_from(operation).expect("JsonPatch operations are validated"))
+    }
+
+    /// Returns the number of operations.
+    #[inline(always)]
+    pub fn len(&self) -> i32 {
+        self.operations.len()
+    }
+
+    /// Returns `true` if the patch has no operations.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+
+    /// Applies every operation to `target`, or none of them.
+    ///
+    /// The patch is applied to a copy of `target`, which replaces the
+    /// original value only if every operation succeeds.
+    ///
+    /// # Errors
+    ///
+    /// This function returns a [`PatchError`] describing why the first failing
+    /// operation could not be applied, in which case `target` is unchanged.
+    pub fn apply<'t>(&self, target: &mut CJsonRef<'t>) -> Result<(), PatchError>
+    where
+        'json: 't,
+    {
+        let mut scratch = target.duplicate().map_err(|_| PatchError::Allocation)?;
+        scratch.apply_patch(&self.operations)?;
+        target.swap_value(&mut scratch);
+        Ok(())
+    }
+}
+
+impl<'json> TryFrom<CJsonArray<CJson<'json>>> for JsonPatch<'json> {
+    type Error = PatchError;
+
+    fn try_from(operations: CJsonArray<CJson<'json>>) -> Result<Self, Self::Error> {
+        for operation in operations.iter() {
+            Op::try_from(operation)?;
+        }
+        Ok(Self { operations })
+    }
+}
+
+impl<'json> From<JsonPatch<'json>> for CJsonArray<CJson<'json>> {
+    fn from(value: JsonPatch<'json>) -> Self {
+        value.operations
+    }
+}
+
+impl<'json> AsRef<CJsonRef<'json>> for JsonPatch<'json> {
+    fn as_ref(&self) -> &CJsonRef<'json> {
+        self.operations.as_ref()
+    }
+}
+
+impl Display for JsonPatch<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        Display::fmt(&self.operations, f)
+    }
+}
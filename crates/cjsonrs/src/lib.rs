@@ -2,21 +2,37 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 #![forbid(clippy::std_instead_of_core)]
 
+#[cfg(all(feature = "std", target_has_atomic = "ptr"))]
+mod arena;
 mod array;
 mod cjson;
 mod cjsonref;
+mod documents;
 mod error;
+pub mod hooks;
+mod json_patch;
 mod object;
+mod parse;
+mod patch;
+mod pointer;
+mod print;
 mod string;
 #[macro_use]
 mod macros;
-// #[cfg(feature = "serde")]
-// pub mod serde;
+#[cfg(feature = "serde")]
+pub mod serde;
 
 // Re-export module contents
+#[cfg(all(feature = "std", target_has_atomic = "ptr"))]
+pub use arena::*;
 pub use array::*;
 pub use cjson::*;
 pub use cjsonref::*;
+pub use documents::*;
 pub use error::*;
+pub use json_patch::*;
 pub use object::*;
+pub use parse::*;
+pub use pointer::*;
+pub use print::*;
 pub use string::*;
@@ -17,6 +17,7 @@ use core::ptr::NonNull;
 
 use super::CJson;
 use super::CJsonRef;
+use super::CJsonString;
 use super::Error;
 
 /// A guard that wraps a CJson-like value for object operations.
@@ -71,6 +72,25 @@ where
         result
     }
 
+    /// Returns a reference to the value associated with the given UTF-8 key,
+    /// if any.
+    ///
+    /// # Example usage
+    ///
+    /// ```
+    /// use cjsonrs::cjson;
+    ///
+    /// let object = cjson!({ c"clé" => 42 }).unwrap();
+    ///
+    /// assert_eq!(object.get_str("clé").and_then(|value| value.as_number()), Some(42.0));
+    /// assert!(object.get_str("cl\0é").is_none());
+    /// ```
+    #[inline(always)]
+    pub fn get_str(&self, key: &str) -> Option<&CJsonRef<'json>> {
+        let item = find_str(self.inner.as_ref(), key)?;
+        Some(unsafe { CJsonRef::from_ptr(item) })
+    }
+
     /// Returns a iterator over the key-value pairs of the object.
     /// The iterator yields the keys and values of the object in the order
     /// they are stored in the object.
@@ -155,6 +175,14 @@ where
         result
     }
 
+    /// Returns a mutable reference to the value associated with the given
+    /// UTF-8 key, if any.
+    #[inline(always)]
+    pub fn get_str_mut(&mut self, key: &str) -> Option<&mut CJsonRef<'json>> {
+        let item = find_str(self.inner.as_mut(), key)?;
+        Some(unsafe { CJsonRef::from_mut_ptr(item.cast_mut()) })
+    }
+
     /// Removes the value associated with the given key.
     ///
     /// If the key is present, the associated value is removed and returned.
@@ -172,6 +200,23 @@ where
             .map(|ptr| unsafe { CJson::from_raw_parts(ptr, PhantomData::<&'json ()>) })
     }
 
+    /// Removes the value associated with the given UTF-8 key.
+    ///
+    /// If the key is present, the associated value is removed and returned.
+    ///
+    /// If the key is not present, this function returns `None`.
+    #[inline(always)]
+    pub fn remove_str(&mut self, key: &str) -> Option<CJson<'json>> {
+        let cjsonref = self.inner.as_mut();
+        let item = find_str(cjsonref, key)?;
+        let detached = unsafe {
+            cjsonrs_sys::cJSON_DetachItemViaPointer(cjsonref.as_mut_ptr(), item.cast_mut())
+        };
+
+        NonNull::new(detached)
+            .map(|ptr| unsafe { CJson::from_raw_parts(ptr, PhantomData::<&'json ()>) })
+    }
+
     /// Inserts a new key-value pair into the object.
     ///
     /// If the key is already present, the associated value is replaced and
@@ -203,6 +248,61 @@ where
         result
     }
 
+    /// Inserts a new key-value pair with a UTF-8 key into the object.
+    ///
+    /// If the key is already present, the associated value is replaced and
+    /// returned.
+    ///
+    /// If the key is not present, `None` is returned.
+    ///
+    /// # Example usage
+    ///
+    /// ```
+    /// use cjsonrs::CJson;
+    /// use cjsonrs::CJsonObject;
+    /// use cjsonrs::Error;
+    ///
+    /// let mut object = CJsonObject::new()?;
+    /// assert_eq!(object.insert_str("clé", CJson::from_str_value("valeur")?)?, None);
+    /// assert_eq!(object.to_string(), r#"{"clé":"valeur"}"#);
+    /// assert_eq!(object.insert_str("a\0b", CJson::null()?), Err(Error::InteriorNul(1)));
+    /// # Ok::<(), Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function returns [`Error::InteriorNul`] if `key` contains a nul
+    /// byte, in which case the object is left untouched, or an error if the
+    /// key cannot be allocated.
+    #[inline(always)]
+    pub fn insert_str<'a>(
+        &mut self,
+        key: &str,
+        value: impl Into<CJson<'a>>,
+    ) -> Result<Option<CJson<'json>>, Error>
+    where
+        'a: 'json,
+    {
+        let key = CJsonString::copy_from_str(key)?;
+        let value = value.into();
+        let ptr = self.inner.as_mut().as_mut_ptr();
+        let result = self.remove(&*key);
+
+        let item = value.into_raw_parts().as_ptr();
+        let return_code = unsafe { cjsonrs_sys::cJSON_AddItemToObjectCS(ptr, key.as_ptr(), item) };
+
+        // We met all preconditions, so this should never fail.
+        assert_ne!(return_code, 0, "cJSON_AddItemToObjectCS returned an error");
+
+        // The item takes ownership of the key, so it is freed along with it.
+        unsafe {
+            (*item).string = key.into_raw().as_ptr();
+            (*item).type_ &= !(cjsonrs_sys::cJSON_StringIsConst as core::ffi::c_int);
+        }
+
+        Ok(result)
+    }
+
     /// Inserts a new key-value pair into the object, but the key is stored as a
     /// reference.
     ///
@@ -233,6 +333,64 @@ where
 
         result
     }
+
+    /// Sorts the members of the object by key.
+    ///
+    /// Keys are compared byte by byte, so the result matches the order used
+    /// by [`CJsonRef::diff`] but not necessarily the UTF-16 order required by
+    /// [`CJsonRef::canonical`]. If `recursive` is `true`, every nested object
+    /// is sorted as well, including the ones within arrays.
+    ///
+    /// # Example usage
+    ///
+    /// ```
+    /// use cjsonrs::CJson;
+    /// use cjsonrs::CJsonObject;
+    ///
+    /// let cjson: CJson = r#"{"b": {"d": 1, "c": 2}, "a": [{"f": 3, "e": 4}]}"#.parse().unwrap();
+    /// let mut object = CJsonObject::try_from(cjson).unwrap();
+    ///
+    /// object.sort_keys(true);
+    /// assert_eq!(object.to_string(), r#"{"a":[{"e":4,"f":3}],"b":{"c":2,"d":1}}"#);
+    /// ```
+    #[inline(always)]
+    pub fn sort_keys(&mut self, recursive: bool) {
+        self.inner.as_mut().sort_keys(recursive, true);
+    }
+
+    /// Sorts the members of the object by key, ignoring the case of ASCII
+    /// letters.
+    ///
+    /// The order matches the one used by [`CJsonRef::diff_case_insensitive`].
+    /// See [`CJsonObject::sort_keys`] for more information.
+    ///
+    /// # Example usage
+    ///
+    /// ```
+    /// use cjsonrs::CJson;
+    /// use cjsonrs::CJsonObject;
+    ///
+    /// let cjson: CJson = r#"{"b": 1, "C": 2, "a": 3}"#.parse().unwrap();
+    /// let mut object = CJsonObject::try_from(cjson).unwrap();
+    ///
+    /// object.sort_keys_case_insensitive(false);
+    /// assert_eq!(object.to_string(), r#"{"a":3,"b":1,"C":2}"#);
+    /// ```
+    #[inline(always)]
+    pub fn sort_keys_case_insensitive(&mut self, recursive: bool) {
+        self.inner.as_mut().sort_keys(recursive, false);
+    }
+}
+
+/// Returns the first member of `object` whose key is `key`.
+fn find_str(object: &CJsonRef<'_>, key: &str) -> Option<*const cjsonrs_sys::cJSON> {
+    object
+        .iter()
+        .find(|item| {
+            item.name()
+                .is_some_and(|name| name.to_bytes() == key.as_bytes())
+        })
+        .map(CJsonRef::as_ptr)
 }
 
 impl<'json, T: AsRef<CJsonRef<'json>>> PartialEq for CJsonObject<T> {
@@ -254,6 +412,12 @@ impl<'json, T: AsRef<CJsonRef<'json>>> Display for CJsonObject<T> {
     }
 }
 
+impl<'json, T: AsRef<CJsonRef<'json>>> AsRef<CJsonRef<'json>> for CJsonObject<T> {
+    fn as_ref(&self) -> &CJsonRef<'json> {
+        self.inner.as_ref()
+    }
+}
+
 impl<'json> TryFrom<CJson<'json>> for CJsonObject<CJson<'json>> {
     type Error = Error;
 
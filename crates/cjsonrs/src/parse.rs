@@ -0,0 +1,94 @@
+use core::ffi::c_char;
+use core::marker::PhantomData;
+use core::ptr::NonNull;
+
+use super::hooks::Usage;
+use super::CJson;
+use super::Error;
+use super::ParseError;
+
+/// Options for parsing JSON documents into [`CJson`] values.
+///
+/// # Example usage
+///
+/// ```
+/// use cjsonrs::ParseOptions;
+///
+/// let strict = ParseOptions::new().require_end_of_input(true);
+/// assert!(strict.parse(br#"{"a": 1} garbage"#).is_err());
+///
+/// let (cjson, remainder) = ParseOptions::new().parse(br#"{"a": 1} garbage"#).unwrap();
+/// assert!(cjson.is_object());
+/// assert_eq!(remainder, b" garbage");
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ParseOptions {
+    require_end_of_input: bool,
+}
+
+impl ParseOptions {
+    /// Constructs the default [`ParseOptions`], which match the behaviour of
+    /// [`CJson::from_slice`].
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self {
+            require_end_of_input: false,
+        }
+    }
+
+    /// Sets whether the document must span the whole input.
+    ///
+    /// When enabled, anything other than whitespace after the document is
+    /// reported as a [`ParseError`] pointing at the first offending byte.
+    /// Following cJSON, every byte lower or equal than `0x20` is treated as
+    /// whitespace.
+    #[inline(always)]
+    pub const fn require_end_of_input(mut self, require: bool) -> Self {
+        self.require_end_of_input = require;
+        self
+    }
+
+    /// Parses the first JSON document of `input`, returning it alongside the
+    /// unconsumed remainder of `input`.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the input is not valid JSON, if
+    /// allocation fails or if the options are not met.
+    #[inline(always)]
+    pub fn parse<'a>(&self, input: &'a [u8]) -> Result<(CJson<'static>, &'a [u8]), Error> {
+        let _usage = Usage::acquire();
+        let mut parse_end = core::ptr::null();
+        let cjson = unsafe {
+            cjsonrs_sys::cJSON_ParseWithLengthOpts(
+                input.as_ptr() as _,
+                input.len(),
+                &mut parse_end,
+                0,
+            )
+        };
+        let offset = parse_end_offset(input, parse_end);
+
+        let Some(ptr) = NonNull::new(cjson) else {
+            return Err(ParseError::from_offset(input, offset).into());
+        };
+        let cjson = unsafe { CJson::from_raw_parts(ptr, PhantomData::<&'static ()>) };
+        let remainder = &input[offset..];
+
+        if self.require_end_of_input {
+            if let Some(garbage) = remainder.iter().position(|&b| b > b' ') {
+                return Err(ParseError::from_offset(input, offset + garbage).into());
+            }
+        }
+
+        Ok((cjson, remainder))
+    }
+}
+
+/// Converts the `return_parse_end` pointer reported by cJSON into an offset
+/// within `input`.
+#[inline(always)]
+fn parse_end_offset(input: &[u8], parse_end: *const c_char) -> usize {
+    let offset = (parse_end as usize).saturating_sub(input.as_ptr() as usize);
+    offset.min(input.len())
+}
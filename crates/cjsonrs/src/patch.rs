@@ -0,0 +1,327 @@
+use core::ffi::c_int;
+use core::ffi::CStr;
+use core::marker::PhantomData;
+use core::ptr::NonNull;
+
+use super::hooks::Usage;
+use super::CJson;
+use super::CJsonArray;
+use super::CJsonRef;
+use super::Error;
+use super::Op;
+use super::PatchError;
+
+impl<'json> CJsonRef<'json> {
+    /// Generates a [JSON Patch](https://www.rfc-editor.org/rfc/rfc6902) that
+    /// transforms this value into `other`. Object keys are compared
+    /// case-sensitively.
+    ///
+    /// cJSON sorts the keys of every object it compares, which is why both
+    /// values are borrowed mutably. The generated operations copy values out
+    /// of `other`.
+    ///
+    /// # Example usage
+    ///
+    /// ```
+    /// use cjsonrs::CJson;
+    ///
+    /// let mut from: CJson = r#"{"state": "off", "brightness": 10}"#.parse().unwrap();
+    /// let mut to: CJson = r#"{"state": "on", "brightness": 10}"#.parse().unwrap();
+    ///
+    /// let patch = from.diff(&mut to).unwrap();
+    /// assert_eq!(patch.to_string(), r#"[{"op":"replace","path":"/state","value":"on"}]"#);
+    ///
+    /// from.apply_patch(&patch).unwrap();
+    /// assert_eq!(from, to);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the patch cannot be allocated.
+    #[inline(always)]
+    pub fn diff<'o>(&mut self, other: &mut CJsonRef<'o>) -> Result<CJsonArray<CJson<'o>>, Error> {
+        generate_patches(self, other, true)
+    }
+
+    /// Generates a JSON Patch that transforms this value into `other`,
+    /// ignoring the case of object keys.
+    ///
+    /// See [`CJsonRef::diff`] for more information.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the patch cannot be allocated.
+    #[inline(always)]
+    pub fn diff_case_insensitive<'o>(
+        &mut self,
+        other: &mut CJsonRef<'o>,
+    ) -> Result<CJsonArray<CJson<'o>>, Error> {
+        generate_patches(self, other, false)
+    }
+
+    /// Applies a JSON Patch to this value. Object keys are compared
+    /// case-sensitively.
+    ///
+    /// Operations are applied in order and cJSON stops at the first failing
+    /// one, leaving the previous operations applied. Malformed operations are
+    /// detected upfront, in which case nothing is applied.
+    ///
+    /// # Errors
+    ///
+    /// This function returns a [`PatchError`] describing why the first failing
+    /// operation could not be applied.
+    #[inline(always)]
+    pub fn apply_patch<'p>(&mut self, patch: impl AsRef<CJsonRef<'p>>) -> Result<(), PatchError>
+    where
+        'p: 'json,
+    {
+        apply_patches(self, patch.as_ref(), true)
+    }
+
+    /// Applies a JSON Patch to this value, ignoring the case of object keys.
+    ///
+    /// See [`CJsonRef::apply_patch`] for more information.
+    ///
+    /// # Errors
+    ///
+    /// This function returns a [`PatchError`] describing why the first failing
+    /// operation could not be applied.
+    #[inline(always)]
+    pub fn apply_patch_case_insensitive<'p>(
+        &mut self,
+        patch: impl AsRef<CJsonRef<'p>>,
+    ) -> Result<(), PatchError>
+    where
+        'p: 'json,
+    {
+        apply_patches(self, patch.as_ref(), false)
+    }
+}
+
+impl<'json> CJson<'json> {
+    /// Applies a [JSON Merge Patch](https://www.rfc-editor.org/rfc/rfc7386)
+    /// to this value. Object keys are compared case-sensitively.
+    ///
+    /// This function takes ownership of the value, as cJSON may replace the
+    /// root entirely (e.g. when `patch` is not an object).
+    ///
+    /// # Example usage
+    ///
+    /// ```
+    /// use cjsonrs::CJson;
+    ///
+    /// let config: CJson = r#"{"level": "info", "targets": {"file": true}}"#.parse().unwrap();
+    /// let patch: CJson = r#"{"level": "debug", "targets": {"file": null}}"#.parse().unwrap();
+    ///
+    /// let config = config.merge_patch(&patch).unwrap();
+    /// assert_eq!(config.to_string(), r#"{"level":"debug","targets":{}}"#);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if allocation fails, in which case the
+    /// value is lost.
+    #[inline(always)]
+    pub fn merge_patch<'p>(self, patch: &CJsonRef<'p>) -> Result<CJson<'json>, Error>
+    where
+        'p: 'json,
+    {
+        merge_patch(self, patch, true)
+    }
+
+    /// Applies a JSON Merge Patch to this value, ignoring the case of object
+    /// keys.
+    ///
+    /// See [`CJson::merge_patch`] for more information.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if allocation fails, in which case the
+    /// value is lost.
+    #[inline(always)]
+    pub fn merge_patch_case_insensitive<'p>(
+        self,
+        patch: &CJsonRef<'p>,
+    ) -> Result<CJson<'json>, Error>
+    where
+        'p: 'json,
+    {
+        merge_patch(self, patch, false)
+    }
+}
+
+impl CJsonRef<'_> {
+    /// Generates a JSON Merge Patch that transforms this value into `other`.
+    /// Object keys are compared case-sensitively.
+    ///
+    /// cJSON sorts the keys of every object it compares, which is why both
+    /// values are borrowed mutably. If both values are equal, the patch is an
+    /// empty object.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the patch cannot be allocated.
+    #[inline(always)]
+    pub fn merge_diff<'o>(&mut self, other: &mut CJsonRef<'o>) -> Result<CJson<'o>, Error> {
+        generate_merge_patch(self, other, true)
+    }
+
+    /// Generates a JSON Merge Patch that transforms this value into `other`,
+    /// ignoring the case of object keys.
+    ///
+    /// See [`CJsonRef::merge_diff`] for more information.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the patch cannot be allocated.
+    #[inline(always)]
+    pub fn merge_diff_case_insensitive<'o>(
+        &mut self,
+        other: &mut CJsonRef<'o>,
+    ) -> Result<CJson<'o>, Error> {
+        generate_merge_patch(self, other, false)
+    }
+}
+
+fn generate_patches<'o>(
+    from: &mut CJsonRef<'_>,
+    to: &mut CJsonRef<'o>,
+    case_sensitive: bool,
+) -> Result<CJsonArray<CJson<'o>>, Error> {
+    let (from, to) = (from.as_mut_ptr(), to.as_mut_ptr());
+    let ptr = unsafe {
+        if case_sensitive {
+            cjsonrs_sys::cJSONUtils_GeneratePatchesCaseSensitive(from, to)
+        } else {
+            cjsonrs_sys::cJSONUtils_GeneratePatches(from, to)
+        }
+    };
+
+    let ptr = NonNull::new(ptr).ok_or(Error::Allocation)?;
+    let cjson = unsafe { CJson::from_raw_parts(ptr, PhantomData::<&'o ()>) };
+    Ok(unsafe { CJsonArray::from_raw_parts(cjson) })
+}
+
+fn apply_patches(
+    target: &mut CJsonRef<'_>,
+    patch: &CJsonRef<'_>,
+    case_sensitive: bool,
+) -> Result<(), PatchError> {
+    // cJSON also reports a failed `test` operation as 1, so non-arrays must
+    // be told apart beforehand.
+    if !patch.is_array() {
+        return Err(PatchError::Malformed);
+    }
+    // cJSON does not check every member it reads (e.g. a non-string `from`
+    // is dereferenced as a null pointer), so operations are validated first.
+    for operation in patch.iter() {
+        let operation = Op::from_operation(operation, case_sensitive)?;
+        if is_root_operation(&operation) {
+            return Err(PatchError::RootOperation);
+        }
+    }
+
+    let (target, patch) = (target.as_mut_ptr(), patch.as_ptr());
+    let status = unsafe {
+        if case_sensitive {
+            cjsonrs_sys::cJSONUtils_ApplyPatchesCaseSensitive(target, patch)
+        } else {
+            cjsonrs_sys::cJSONUtils_ApplyPatches(target, patch)
+        }
+    };
+
+    match status {
+        0 => Ok(()),
+        status => Err(patch_error(status)),
+    }
+}
+
+fn merge_patch<'json>(
+    target: CJson<'json>,
+    patch: &CJsonRef<'_>,
+    case_sensitive: bool,
+) -> Result<CJson<'json>, Error> {
+    // The target stops being tracked once its ownership is handed to cJSON.
+    let _usage = Usage::acquire();
+    let (target, patch) = (target.into_raw_parts().as_ptr(), patch.as_ptr());
+    // cJSON deletes the target when it cannot be patched in place, returning
+    // a new root instead.
+    let ptr = unsafe {
+        if case_sensitive {
+            cjsonrs_sys::cJSONUtils_MergePatchCaseSensitive(target, patch)
+        } else {
+            cjsonrs_sys::cJSONUtils_MergePatch(target, patch)
+        }
+    };
+
+    let ptr = NonNull::new(ptr).ok_or(Error::Allocation)?;
+    Ok(unsafe { CJson::from_raw_parts(ptr, PhantomData::<&'json ()>) })
+}
+
+fn generate_merge_patch<'o>(
+    from: &mut CJsonRef<'_>,
+    to: &mut CJsonRef<'o>,
+    case_sensitive: bool,
+) -> Result<CJson<'o>, Error> {
+    let (from, to) = (from.as_mut_ptr(), to.as_mut_ptr());
+    let ptr = unsafe {
+        if case_sensitive {
+            cjsonrs_sys::cJSONUtils_GenerateMergePatchCaseSensitive(from, to)
+        } else {
+            cjsonrs_sys::cJSONUtils_GenerateMergePatch(from, to)
+        }
+    };
+
+    if let Some(ptr) = NonNull::new(ptr) {
+        return Ok(unsafe { CJson::from_raw_parts(ptr, PhantomData::<&'o ()>) });
+    }
+
+    // cJSON returns NULL both when allocation fails and when there are no
+    // changes, which is expressed as an empty patch.
+    let equal = unsafe { cjsonrs_sys::cJSON_Compare(from, to, c_int::from(case_sensitive)) };
+    if equal != 0 {
+        CJson::object()
+    } else {
+        Err(Error::Allocation)
+    }
+}
+
+/// Returns `true` if `operation` makes cJSON overwrite the target in place,
+/// which would break its links with its siblings and free borrowed strings.
+fn is_root_operation(operation: &Op<'_, '_>) -> bool {
+    matches!(
+        operation,
+        Op::Add { .. } | Op::Replace { .. } | Op::Remove { .. }
+    ) && operation.path().is_empty()
+}
+
+pub(super) fn member<'a, 'json>(
+    object: &'a CJsonRef<'json>,
+    key: &CStr,
+    case_sensitive: bool,
+) -> Option<&'a CJsonRef<'json>> {
+    let ptr = object.as_ptr();
+    let cjson = unsafe {
+        if case_sensitive {
+            cjsonrs_sys::cJSON_GetObjectItemCaseSensitive(ptr, key.as_ptr())
+        } else {
+            cjsonrs_sys::cJSON_GetObjectItem(ptr, key.as_ptr())
+        }
+    };
+
+    if cjson.is_null() {
+        None
+    } else {
+        Some(unsafe { CJsonRef::from_ptr(cjson) })
+    }
+}
+
+/// Maps the status codes returned by `cJSONUtils_ApplyPatches`.
+fn patch_error(status: c_int) -> PatchError {
+    match status {
+        1 => PatchError::TestFailed,
+        5 | 9 | 10 | 11 | 13 => PatchError::MissingPath,
+        6 | 8 => PatchError::Allocation,
+        _ => PatchError::Malformed,
+    }
+}
@@ -0,0 +1,219 @@
+use core::ffi::CStr;
+use core::fmt;
+use core::fmt::Display;
+use core::fmt::Write;
+use core::ptr::NonNull;
+
+use super::CJsonRef;
+use super::CJsonString;
+
+impl<'json> CJsonRef<'json> {
+    /// Looks up a value by its [JSON Pointer](https://www.rfc-editor.org/rfc/rfc6901),
+    /// such as `/devices/3/state`. Object keys are compared case-sensitively.
+    ///
+    /// The empty pointer refers to the value itself. Keys containing `~` or
+    /// `/` must be escaped, see [`escape_pointer_token`].
+    ///
+    /// # Example usage
+    ///
+    /// ```
+    /// use cjsonrs::CJson;
+    ///
+    /// let cjson: CJson = r#"{"devices": [{"state": "on"}]}"#.parse().unwrap();
+    /// let state = cjson.pointer(c"/devices/0/state").unwrap();
+    /// assert_eq!(state.as_c_string(), Some(c"on"));
+    /// ```
+    #[inline(always)]
+    pub fn pointer(&self, pointer: impl AsRef<CStr>) -> Option<&CJsonRef<'json>> {
+        let pointer = pointer.as_ref();
+        if !is_valid_pointer(pointer) {
+            return None;
+        }
+
+        let ptr = self.as_ptr().cast_mut();
+        let cjson =
+            unsafe { cjsonrs_sys::cJSONUtils_GetPointerCaseSensitive(ptr, pointer.as_ptr()) };
+
+        if cjson.is_null() {
+            None
+        } else {
+            Some(unsafe { CJsonRef::from_ptr(cjson) })
+        }
+    }
+
+    /// Looks up a value by its JSON Pointer, ignoring the case of object keys.
+    ///
+    /// See [`CJsonRef::pointer`] for more information.
+    #[inline(always)]
+    pub fn pointer_case_insensitive(&self, pointer: impl AsRef<CStr>) -> Option<&CJsonRef<'json>> {
+        let pointer = pointer.as_ref();
+        if !is_valid_pointer(pointer) {
+            return None;
+        }
+
+        let ptr = self.as_ptr().cast_mut();
+        let cjson = unsafe { cjsonrs_sys::cJSONUtils_GetPointer(ptr, pointer.as_ptr()) };
+
+        if cjson.is_null() {
+            None
+        } else {
+            Some(unsafe { CJsonRef::from_ptr(cjson) })
+        }
+    }
+
+    /// Looks up a mutable value by its JSON Pointer. Object keys are compared
+    /// case-sensitively.
+    ///
+    /// See [`CJsonRef::pointer`] for more information.
+    #[inline(always)]
+    pub fn pointer_mut(&mut self, pointer: impl AsRef<CStr>) -> Option<&mut CJsonRef<'json>> {
+        let pointer = pointer.as_ref();
+        if !is_valid_pointer(pointer) {
+            return None;
+        }
+
+        let ptr = self.as_mut_ptr();
+        let cjson =
+            unsafe { cjsonrs_sys::cJSONUtils_GetPointerCaseSensitive(ptr, pointer.as_ptr()) };
+
+        if cjson.is_null() {
+            None
+        } else {
+            Some(unsafe { CJsonRef::from_mut_ptr(cjson) })
+        }
+    }
+
+    /// Returns the JSON Pointer of `target` relative to this value.
+    ///
+    /// `target` is matched by identity rather than by value, so any reference
+    /// obtained while walking this tree (e.g. through iterators) can be
+    /// located. Returns [`None`] if `target` is not part of this tree or if
+    /// the pointer cannot be allocated.
+    ///
+    /// # Example usage
+    ///
+    /// ```
+    /// use cjsonrs::CJson;
+    ///
+    /// let cjson: CJson = r#"{"devices": [{"state": "on"}, {"state": 42}]}"#.parse().unwrap();
+    /// let devices = cjson.pointer(c"/devices").unwrap();
+    /// let invalid = devices
+    ///     .into_iter()
+    ///     .flat_map(|device| device.into_iter())
+    ///     .find(|state| !state.is_string())
+    ///     .unwrap();
+    ///
+    /// let path = cjson.path_to(invalid).unwrap();
+    /// assert_eq!(&*path, c"/devices/1/state");
+    /// ```
+    #[inline(always)]
+    pub fn path_to(&self, target: &CJsonRef<'_>) -> Option<CJsonString> {
+        let ptr = unsafe {
+            cjsonrs_sys::cJSONUtils_FindPointerFromObjectTo(self.as_ptr(), target.as_ptr())
+        };
+
+        let ptr = NonNull::new(ptr)?;
+        let len = unsafe { CStr::from_ptr(ptr.as_ptr()) }
+            .to_bytes_with_nul()
+            .len();
+        Some(unsafe { CJsonString::from_raw_parts(ptr, len) })
+    }
+}
+
+/// Returns `true` if `pointer` is a well-formed JSON Pointer.
+///
+/// cJSON treats any pointer not starting with `/` as the empty pointer, while
+/// RFC 6901 only allows the empty string.
+#[inline(always)]
+pub(super) fn is_valid_pointer(pointer: &CStr) -> bool {
+    let pointer = pointer.to_bytes();
+    matches!(pointer.first(), None | Some(b'/')) && has_valid_escapes(pointer)
+}
+
+/// Returns `true` if every `~` in `token` is followed by `0` or `1`.
+#[inline(always)]
+fn has_valid_escapes(token: &[u8]) -> bool {
+    let mut bytes = token.iter();
+    while let Some(&b) = bytes.next() {
+        if b == b'~' && !matches!(bytes.next(), Some(b'0' | b'1')) {
+            return false;
+        }
+    }
+    true
+}
+
+/// Escapes a single JSON Pointer reference token, replacing `~` with `~0` and
+/// `/` with `~1`.
+///
+/// The returned value implements [`Display`], so pointers can be built
+/// without intermediate allocations.
+///
+/// # Example usage
+///
+/// ```
+/// use cjsonrs::escape_pointer_token;
+///
+/// let pointer = format!("/units/{}", escape_pointer_token("m/s~2"));
+/// assert_eq!(pointer, "/units/m~1s~02");
+/// ```
+#[inline(always)]
+pub fn escape_pointer_token(token: &str) -> EscapePointerToken<'_> {
+    EscapePointerToken(token)
+}
+
+/// Unescapes a single JSON Pointer reference token, replacing `~1` with `/`
+/// and `~0` with `~`.
+///
+/// Returns [`None`] if the token contains a `~` not followed by `0` or `1`.
+///
+/// # Example usage
+///
+/// ```
+/// use cjsonrs::unescape_pointer_token;
+///
+/// let token = unescape_pointer_token("m~1s~02").unwrap();
+/// assert_eq!(token.to_string(), "m/s~2");
+/// assert!(unescape_pointer_token("~2").is_none());
+/// ```
+#[inline(always)]
+pub fn unescape_pointer_token(token: &str) -> Option<UnescapePointerToken<'_>> {
+    has_valid_escapes(token.as_bytes()).then_some(UnescapePointerToken(token))
+}
+
+/// An escaped JSON Pointer reference token.
+///
+/// This struct is created by [`escape_pointer_token`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EscapePointerToken<'a>(&'a str);
+
+impl Display for EscapePointerToken<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '~' => f.write_str("~0")?,
+                '/' => f.write_str("~1")?,
+                c => f.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An unescaped JSON Pointer reference token.
+///
+/// This struct is created by [`unescape_pointer_token`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct UnescapePointerToken<'a>(&'a str);
+
+impl Display for UnescapePointerToken<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut chars = self.0.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '~' => f.write_char(if chars.next() == Some('1') { '/' } else { '~' })?,
+                c => f.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,778 @@
+#[cfg(feature = "std")]
+use std::fs::File;
+#[cfg(feature = "std")]
+use std::io;
+#[cfg(feature = "std")]
+use std::path::Path;
+
+use core::cmp::Ordering;
+use core::ffi::c_int;
+use core::ffi::CStr;
+use core::fmt;
+use core::fmt::Display;
+use core::fmt::Write;
+
+use super::CJsonRef;
+
+/// Layout options for pretty-printing [`CJsonRef`] values.
+///
+/// The default configuration reproduces cJSON's own pretty format: tab
+/// indentation, a tab after each key, arrays on a single line and no trailing
+/// newline. Empty objects keep cJSON's layout, with the closing brace on its
+/// own line.
+///
+/// # Example usage
+///
+/// ```
+/// use cjsonrs::cjson;
+/// use cjsonrs::CJson;
+/// use cjsonrs::PrettyConfig;
+///
+/// let cjson: CJson = cjson!({c"list" => [1, 2]}).unwrap().into();
+/// let config = PrettyConfig::new()
+///     .indent("  ")
+///     .key_value_separator(": ")
+///     .max_inline_array_len(0)
+///     .trailing_newline(true);
+///
+/// let output = format!("{:#}", cjson.display_with(config));
+/// assert_eq!(output, "{\n  \"list\": [\n    1,\n    2\n  ]\n}\n");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PrettyConfig<'a> {
+    indent: &'a str,
+    newline: &'a str,
+    key_value_separator: &'a str,
+    max_inline_array_len: usize,
+    trailing_newline: bool,
+}
+
+impl<'a> PrettyConfig<'a> {
+    /// Constructs a [`PrettyConfig`] matching [`CJsonRef::to_c_string_pretty`].
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self {
+            indent: "\t",
+            newline: "\n",
+            key_value_separator: ":\t",
+            max_inline_array_len: usize::MAX,
+            trailing_newline: false,
+        }
+    }
+
+    /// Sets the string written once per nesting level, such as `"  "`.
+    #[inline(always)]
+    pub const fn indent(mut self, indent: &'a str) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    /// Sets the line terminator, such as `"\r\n"`.
+    #[inline(always)]
+    pub const fn newline(mut self, newline: &'a str) -> Self {
+        self.newline = newline;
+        self
+    }
+
+    /// Sets the string written between object keys and their values.
+    #[inline(always)]
+    pub const fn key_value_separator(mut self, separator: &'a str) -> Self {
+        self.key_value_separator = separator;
+        self
+    }
+
+    /// Sets the maximum number of items an array may hold to be printed on a
+    /// single line. Longer arrays print one item per line.
+    #[inline(always)]
+    pub const fn max_inline_array_len(mut self, len: usize) -> Self {
+        self.max_inline_array_len = len;
+        self
+    }
+
+    /// Sets whether a newline is written after the root value.
+    #[inline(always)]
+    pub const fn trailing_newline(mut self, trailing_newline: bool) -> Self {
+        self.trailing_newline = trailing_newline;
+        self
+    }
+}
+
+impl Default for PrettyConfig<'_> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Helper struct for printing [`CJsonRef`] values with a [`PrettyConfig`].
+///
+/// Like [`CJsonRef`] itself, the alternate form (`{:#}`) pretty-prints the
+/// value, while the regular form prints compact JSON.
+///
+/// This struct is created by [`CJsonRef::display_with`].
+#[derive(Debug, Clone, Copy)]
+pub struct DisplayWith<'a, 'json> {
+    cjson: &'a CJsonRef<'json>,
+    config: PrettyConfig<'a>,
+}
+
+impl Display for DisplayWith<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pretty = f.alternate().then_some(self.config);
+        Printer::new(&mut FmtSink(f), pretty).print(self.cjson)
+    }
+}
+
+/// Helper struct for printing [`CJsonRef`] values in the canonical form
+/// defined by [RFC 8785](https://www.rfc-editor.org/rfc/rfc8785) (JCS).
+///
+/// Formatting fails if the value holds non-finite numbers, strings or keys
+/// that are not valid UTF-8, or duplicate keys.
+///
+/// This struct is created by [`CJsonRef::canonical`].
+#[derive(Debug, Clone, Copy)]
+pub struct Canonical<'a, 'json> {
+    cjson: &'a CJsonRef<'json>,
+}
+
+impl Display for Canonical<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Printer::canonical(&mut FmtSink(f)).print(self.cjson)
+    }
+}
+
+impl<'json> CJsonRef<'json> {
+    /// Returns a [`Display`] implementation that prints this value in the
+    /// canonical form defined by [RFC 8785](https://www.rfc-editor.org/rfc/rfc8785),
+    /// so that equal values always produce identical bytes.
+    ///
+    /// Object members are printed sorted by the UTF-16 code units of their
+    /// keys, numbers use their shortest round-trip representation and no
+    /// whitespace is emitted. The value itself is not modified. Members are
+    /// sorted without allocating, which takes quadratic time on the size of
+    /// each object.
+    ///
+    /// # Example usage
+    ///
+    /// ```
+    /// use cjsonrs::CJson;
+    ///
+    /// let cjson: CJson = r#"{"b": [1.50, 1e21], "a": "\u00e9"}"#.parse().unwrap();
+    /// assert_eq!(cjson.canonical().to_string(), r#"{"a":"é","b":[1.5,1e+21]}"#);
+    /// ```
+    #[inline(always)]
+    pub fn canonical(&self) -> Canonical<'_, 'json> {
+        Canonical { cjson: self }
+    }
+
+    /// Prints the underlying [`cjsonrs_sys::cJSON`] object into a
+    /// [`std::io::Write`] in the canonical form defined by RFC 8785.
+    ///
+    /// See [`CJsonRef::canonical`] for more information.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the writer fails or if the value
+    /// cannot be canonicalized, see [`Canonical`].
+    #[cfg(feature = "std")]
+    #[inline(always)]
+    pub fn to_canonical_io_writer<W>(&self, writer: &mut W) -> io::Result<()>
+    where
+        W: io::Write + ?Sized,
+    {
+        Printer::canonical(&mut IoSink(writer)).print(self)
+    }
+
+    /// Serializes the underlying [`cjsonrs_sys::cJSON`] object into a
+    /// [`core::fmt::Write`], without building an intermediate C string.
+    ///
+    /// The output is byte-identical to [`CJsonRef::to_c_string`] or, if
+    /// `pretty` is set, to [`CJsonRef::to_c_string_pretty`].
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the writer fails, if a string is not
+    /// valid UTF-8 or if the value is malformed.
+    #[inline(always)]
+    pub fn to_fmt_writer<W>(&self, writer: &mut W, pretty: bool) -> fmt::Result
+    where
+        W: fmt::Write + ?Sized,
+    {
+        let pretty = pretty.then(PrettyConfig::new);
+        Printer::new(&mut FmtSink(writer), pretty).print(self)
+    }
+
+    /// Pretty-prints the underlying [`cjsonrs_sys::cJSON`] object into a
+    /// [`core::fmt::Write`], following `config`.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the writer fails, if a string is not
+    /// valid UTF-8 or if the value is malformed.
+    #[inline(always)]
+    pub fn to_fmt_writer_with<W>(&self, writer: &mut W, config: &PrettyConfig<'_>) -> fmt::Result
+    where
+        W: fmt::Write + ?Sized,
+    {
+        Printer::new(&mut FmtSink(writer), Some(*config)).print(self)
+    }
+
+    /// Returns a [`Display`] implementation that pretty-prints this value
+    /// following `config` when formatted with `{:#}`.
+    #[inline(always)]
+    pub fn display_with<'a>(&'a self, config: PrettyConfig<'a>) -> DisplayWith<'a, 'json> {
+        DisplayWith {
+            cjson: self,
+            config,
+        }
+    }
+
+    /// Serializes the underlying [`cjsonrs_sys::cJSON`] object into a
+    /// [`std::io::Write`], without building an intermediate C string.
+    ///
+    /// The output is byte-identical to [`CJsonRef::to_c_string`] or, if
+    /// `pretty` is set, to [`CJsonRef::to_c_string_pretty`]. The output is
+    /// written in small chunks, so unbuffered writers should be wrapped in a
+    /// [`std::io::BufWriter`].
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the writer fails or if the value is
+    /// malformed.
+    #[cfg(feature = "std")]
+    #[inline(always)]
+    pub fn to_io_writer<W>(&self, writer: &mut W, pretty: bool) -> io::Result<()>
+    where
+        W: io::Write + ?Sized,
+    {
+        let pretty = pretty.then(PrettyConfig::new);
+        Printer::new(&mut IoSink(writer), pretty).print(self)
+    }
+
+    /// Pretty-prints the underlying [`cjsonrs_sys::cJSON`] object into a
+    /// [`std::io::Write`], following `config`.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the writer fails or if the value is
+    /// malformed.
+    #[cfg(feature = "std")]
+    #[inline(always)]
+    pub fn to_io_writer_with<W>(&self, writer: &mut W, config: &PrettyConfig<'_>) -> io::Result<()>
+    where
+        W: io::Write + ?Sized,
+    {
+        Printer::new(&mut IoSink(writer), Some(*config)).print(self)
+    }
+
+    /// Writes the underlying [`cjsonrs_sys::cJSON`] object into the file at
+    /// `path`, replacing its contents.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the file cannot be written or if the
+    /// value is malformed.
+    #[cfg(feature = "std")]
+    #[inline(always)]
+    pub fn write_to_file<P: AsRef<Path>>(&self, path: P, pretty: bool) -> io::Result<()> {
+        let pretty = pretty.then(PrettyConfig::new);
+        write_file(self, path.as_ref(), pretty)
+    }
+
+    /// Pretty-prints the underlying [`cjsonrs_sys::cJSON`] object into the
+    /// file at `path` following `config`, replacing its contents.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the file cannot be written or if the
+    /// value is malformed.
+    #[cfg(feature = "std")]
+    #[inline(always)]
+    pub fn write_to_file_with<P: AsRef<Path>>(
+        &self,
+        path: P,
+        config: &PrettyConfig<'_>,
+    ) -> io::Result<()> {
+        write_file(self, path.as_ref(), Some(*config))
+    }
+
+    /// Serializes the underlying [`cjsonrs_sys::cJSON`] object into an
+    /// [`embedded_io::Write`], without building an intermediate C string.
+    ///
+    /// The output is byte-identical to [`CJsonRef::to_c_string`] or, if
+    /// `pretty` is set, to [`CJsonRef::to_c_string_pretty`].
+    ///
+    /// # Errors
+    ///
+    /// This function returns [`embedded_io::WriteFmtError::Other`] if the
+    /// writer fails, or [`embedded_io::WriteFmtError::FmtError`] if the value
+    /// is malformed.
+    #[cfg(feature = "embedded-io")]
+    #[inline(always)]
+    pub fn to_embedded_io_writer<W>(
+        &self,
+        writer: &mut W,
+        pretty: bool,
+    ) -> Result<(), embedded_io::WriteFmtError<W::Error>>
+    where
+        W: embedded_io::Write + ?Sized,
+    {
+        let pretty = pretty.then(PrettyConfig::new);
+        Printer::new(&mut EmbeddedIoSink(writer), pretty).print(self)
+    }
+
+    /// Pretty-prints the underlying [`cjsonrs_sys::cJSON`] object into an
+    /// [`embedded_io::Write`], following `config`.
+    ///
+    /// # Errors
+    ///
+    /// This function returns [`embedded_io::WriteFmtError::Other`] if the
+    /// writer fails, or [`embedded_io::WriteFmtError::FmtError`] if the value
+    /// is malformed.
+    #[cfg(feature = "embedded-io")]
+    #[inline(always)]
+    pub fn to_embedded_io_writer_with<W>(
+        &self,
+        writer: &mut W,
+        config: &PrettyConfig<'_>,
+    ) -> Result<(), embedded_io::WriteFmtError<W::Error>>
+    where
+        W: embedded_io::Write + ?Sized,
+    {
+        Printer::new(&mut EmbeddedIoSink(writer), Some(*config)).print(self)
+    }
+}
+
+#[cfg(feature = "std")]
+fn write_file(
+    cjson: &CJsonRef<'_>,
+    path: &Path,
+    pretty: Option<PrettyConfig<'_>>,
+) -> io::Result<()> {
+    let mut writer = io::BufWriter::new(File::create(path)?);
+    Printer::new(&mut IoSink(&mut writer), pretty).print(cjson)?;
+    io::Write::flush(&mut writer)
+}
+
+/// A byte oriented output for [`Printer`].
+trait Sink {
+    type Error;
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), Self::Error>;
+
+    /// Returns the error reported for values cJSON would refuse to print.
+    fn malformed(&self) -> Self::Error;
+}
+
+struct FmtSink<'w, W: ?Sized>(&'w mut W);
+
+impl<W: fmt::Write + ?Sized> Sink for FmtSink<'_, W> {
+    type Error = fmt::Error;
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> fmt::Result {
+        // Escape sequences are ASCII, so chunks never split a UTF-8 sequence.
+        let s = core::str::from_utf8(bytes).map_err(|_| fmt::Error)?;
+        self.0.write_str(s)
+    }
+
+    fn malformed(&self) -> fmt::Error {
+        fmt::Error
+    }
+}
+
+#[cfg(feature = "std")]
+struct IoSink<'w, W: ?Sized>(&'w mut W);
+
+#[cfg(feature = "std")]
+impl<W: io::Write + ?Sized> Sink for IoSink<'_, W> {
+    type Error = io::Error;
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.0.write_all(bytes)
+    }
+
+    fn malformed(&self) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, "malformed cJSON value")
+    }
+}
+
+#[cfg(feature = "embedded-io")]
+struct EmbeddedIoSink<'w, W: ?Sized>(&'w mut W);
+
+#[cfg(feature = "embedded-io")]
+impl<W: embedded_io::Write + ?Sized> Sink for EmbeddedIoSink<'_, W> {
+    type Error = embedded_io::WriteFmtError<W::Error>;
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.0
+            .write_all(bytes)
+            .map_err(embedded_io::WriteFmtError::Other)
+    }
+
+    fn malformed(&self) -> Self::Error {
+        embedded_io::WriteFmtError::FmtError
+    }
+}
+
+/// A port of cJSON's printer, writing directly into a [`Sink`].
+///
+/// Compact output is produced when no [`PrettyConfig`] is given. The
+/// canonical mode follows RFC 8785 instead of cJSON.
+struct Printer<'s, 'c, S> {
+    sink: &'s mut S,
+    pretty: Option<PrettyConfig<'c>>,
+    canonical: bool,
+}
+
+impl<'s, 'c, S: Sink> Printer<'s, 'c, S> {
+    fn new(sink: &'s mut S, pretty: Option<PrettyConfig<'c>>) -> Self {
+        Self {
+            sink,
+            pretty,
+            canonical: false,
+        }
+    }
+
+    fn canonical(sink: &'s mut S) -> Self {
+        Self {
+            sink,
+            pretty: None,
+            canonical: true,
+        }
+    }
+
+    fn print(&mut self, cjson: &CJsonRef<'_>) -> Result<(), S::Error> {
+        self.value(cjson, 0)?;
+        match self.pretty {
+            Some(config) if config.trailing_newline => self.write_str(config.newline),
+            _ => Ok(()),
+        }
+    }
+
+    fn write_str(&mut self, s: &str) -> Result<(), S::Error> {
+        self.sink.write_bytes(s.as_bytes())
+    }
+
+    fn value(&mut self, cjson: &CJsonRef<'_>, depth: usize) -> Result<(), S::Error> {
+        if cjson.is_null() {
+            self.sink.write_bytes(b"null")
+        } else if let Some(b) = cjson.as_bool() {
+            self.sink.write_bytes(if b { b"true" } else { b"false" })
+        } else if let Some(n) = cjson.as_number() {
+            let mut buffer = NumberBuffer::new();
+            let formatted = if self.canonical {
+                format_shortest(n, &mut buffer)
+            } else {
+                format_number(n, cjson.valueint(), &mut buffer)
+            };
+            formatted.map_err(|_| self.sink.malformed())?;
+            self.sink.write_bytes(buffer.as_bytes())
+        } else if let Some(s) = cjson.as_c_string() {
+            if self.canonical && s.to_str().is_err() {
+                return Err(self.sink.malformed());
+            }
+            self.string(s.to_bytes())
+        } else if let Some(raw) = cjson.as_raw() {
+            // Raw fragments are printed verbatim, so they cannot be
+            // canonicalized.
+            if self.canonical {
+                return Err(self.sink.malformed());
+            }
+            self.sink.write_bytes(raw.to_bytes())
+        } else if cjson.is_array() {
+            self.array(cjson, depth)
+        } else if cjson.is_object() && self.canonical {
+            self.canonical_object(cjson, depth)
+        } else if cjson.is_object() {
+            self.object(cjson, depth)
+        } else {
+            Err(self.sink.malformed())
+        }
+    }
+
+    fn string(&mut self, s: &[u8]) -> Result<(), S::Error> {
+        self.sink.write_bytes(b"\"")?;
+
+        let mut unescaped = 0;
+        let mut unicode = *b"\\u0000";
+        for (i, &b) in s.iter().enumerate() {
+            let escaped: &[u8] = match b {
+                b'"' => b"\\\"",
+                b'\\' => b"\\\\",
+                0x08 => b"\\b",
+                0x0c => b"\\f",
+                b'\n' => b"\\n",
+                b'\r' => b"\\r",
+                b'\t' => b"\\t",
+                0..0x20 => {
+                    const HEX: &[u8; 16] = b"0123456789abcdef";
+                    unicode[4] = HEX[usize::from(b >> 4)];
+                    unicode[5] = HEX[usize::from(b & 0xf)];
+                    &unicode
+                }
+                _ => continue,
+            };
+            self.sink.write_bytes(&s[unescaped..i])?;
+            self.sink.write_bytes(escaped)?;
+            unescaped = i + 1;
+        }
+
+        self.sink.write_bytes(&s[unescaped..])?;
+        self.sink.write_bytes(b"\"")
+    }
+
+    fn array(&mut self, cjson: &CJsonRef<'_>, depth: usize) -> Result<(), S::Error> {
+        let depth = depth + 1;
+        let multiline = match self.pretty {
+            Some(config) => cjson.len() as usize > config.max_inline_array_len,
+            None => false,
+        };
+
+        self.sink.write_bytes(b"[")?;
+        for (i, item) in cjson.iter().enumerate() {
+            if i > 0 {
+                let inline = self.pretty.is_some() && !multiline;
+                self.sink.write_bytes(if inline { b", " } else { b"," })?;
+            }
+            if multiline {
+                self.newline()?;
+                self.indent(depth)?;
+            }
+            self.value(item, depth)?;
+        }
+        if multiline {
+            self.newline()?;
+            self.indent(depth - 1)?;
+        }
+        self.sink.write_bytes(b"]")
+    }
+
+    fn object(&mut self, cjson: &CJsonRef<'_>, depth: usize) -> Result<(), S::Error> {
+        let depth = depth + 1;
+        self.sink.write_bytes(b"{")?;
+        self.newline()?;
+
+        let mut iter = cjson.iter().peekable();
+        while let Some(item) = iter.next() {
+            self.indent(depth)?;
+            self.string(item.name().map_or(b"", |name| name.to_bytes()))?;
+            match self.pretty {
+                Some(config) => self.write_str(config.key_value_separator)?,
+                None => self.sink.write_bytes(b":")?,
+            }
+            self.value(item, depth)?;
+            if iter.peek().is_some() {
+                self.sink.write_bytes(b",")?;
+            }
+            self.newline()?;
+        }
+
+        self.indent(depth - 1)?;
+        self.sink.write_bytes(b"}")
+    }
+
+    /// Prints an object with its members sorted as required by RFC 8785.
+    ///
+    /// Members are selected in order one at a time, so no allocation is
+    /// needed to sort them.
+    fn canonical_object(&mut self, cjson: &CJsonRef<'_>, depth: usize) -> Result<(), S::Error> {
+        self.sink.write_bytes(b"{")?;
+
+        let mut previous: Option<&str> = None;
+        loop {
+            let mut next: Option<(&str, &CJsonRef<'_>)> = None;
+            for item in cjson.iter() {
+                let key = item.name().map(CStr::to_str);
+                let Some(Ok(key)) = key else {
+                    return Err(self.sink.malformed());
+                };
+                if previous.is_some_and(|previous| utf16_cmp(key, previous).is_le()) {
+                    continue;
+                }
+                match next.map(|(next, _)| utf16_cmp(key, next)) {
+                    Some(Ordering::Greater) => {}
+                    Some(Ordering::Equal) => return Err(self.sink.malformed()),
+                    Some(Ordering::Less) | None => next = Some((key, item)),
+                }
+            }
+
+            let Some((key, item)) = next else {
+                break;
+            };
+            if previous.is_some() {
+                self.sink.write_bytes(b",")?;
+            }
+            self.string(key.as_bytes())?;
+            self.sink.write_bytes(b":")?;
+            self.value(item, depth + 1)?;
+            previous = Some(key);
+        }
+
+        self.sink.write_bytes(b"}")
+    }
+
+    /// Writes a line break, if pretty-printing.
+    fn newline(&mut self) -> Result<(), S::Error> {
+        match self.pretty {
+            Some(config) => self.write_str(config.newline),
+            None => Ok(()),
+        }
+    }
+
+    /// Writes `depth` indentation levels, if pretty-printing.
+    fn indent(&mut self, depth: usize) -> Result<(), S::Error> {
+        if let Some(config) = self.pretty {
+            for _ in 0..depth {
+                self.write_str(config.indent)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Formats a number exactly like cJSON's `print_number`.
+fn format_number(n: f64, valueint: c_int, buffer: &mut NumberBuffer) -> fmt::Result {
+    if n.is_nan() || n.is_infinite() {
+        buffer.write_str("null")
+    } else if n == valueint as f64 {
+        write!(buffer, "{valueint}")
+    } else {
+        // Try 15 significant digits to avoid nonsignificant nonzero digits,
+        // and fall back to 17 if the original number cannot be recovered.
+        format_general(n, 15, buffer)?;
+        let recovered = buffer.as_str().parse::<f64>();
+        if !recovered.is_ok_and(|recovered| approx_eq(recovered, n)) {
+            buffer.clear();
+            format_general(n, 17, buffer)?;
+        }
+        Ok(())
+    }
+}
+
+/// Formats a number like ECMAScript's `Number.prototype.toString`, as
+/// required by RFC 8785.
+fn format_shortest(n: f64, buffer: &mut NumberBuffer) -> fmt::Result {
+    if !n.is_finite() {
+        return Err(fmt::Error);
+    }
+    if n == 0.0 {
+        return buffer.write_str("0");
+    }
+
+    // Rust already produces the shortest round-trip digits, only the layout
+    // differs.
+    let mut scientific = NumberBuffer::new();
+    write!(scientific, "{n:e}")?;
+    let (mantissa, exponent) = scientific.as_str().split_once('e').ok_or(fmt::Error)?;
+    let exponent: i32 = exponent.parse().map_err(|_| fmt::Error)?;
+    if n < 0.0 {
+        buffer.write_char('-')?;
+    }
+
+    let mut digits = NumberBuffer::new();
+    for c in mantissa.chars().filter(char::is_ascii_digit) {
+        digits.write_char(c)?;
+    }
+    let digits = digits.as_str();
+    let (k, n) = (digits.len() as i32, exponent + 1);
+
+    if k <= n && n <= 21 {
+        buffer.write_str(digits)?;
+        (k..n).try_for_each(|_| buffer.write_char('0'))
+    } else if 0 < n && n <= 21 {
+        let (integer, fraction) = digits.split_at(n as usize);
+        write!(buffer, "{integer}.{fraction}")
+    } else if -6 < n && n <= 0 {
+        buffer.write_str("0.")?;
+        (n..0).try_for_each(|_| buffer.write_char('0'))?;
+        buffer.write_str(digits)
+    } else {
+        let (first, rest) = digits.split_at(1);
+        buffer.write_str(first)?;
+        if !rest.is_empty() {
+            write!(buffer, ".{rest}")?;
+        }
+        let exponent = n - 1;
+        let sign = if exponent < 0 { '-' } else { '+' };
+        write!(buffer, "e{sign}{}", exponent.unsigned_abs())
+    }
+}
+
+/// Compares two strings by their UTF-16 code units, as required by RFC 8785.
+fn utf16_cmp(a: &str, b: &str) -> Ordering {
+    a.encode_utf16().cmp(b.encode_utf16())
+}
+
+/// Formats a number like C's `%1.<precision>g` conversion.
+fn format_general(n: f64, precision: usize, buffer: &mut NumberBuffer) -> fmt::Result {
+    let mut scientific = NumberBuffer::new();
+    write!(scientific, "{:.*e}", precision - 1, n)?;
+    let (mantissa, exponent) = scientific.as_str().split_once('e').ok_or(fmt::Error)?;
+    let exponent: i32 = exponent.parse().map_err(|_| fmt::Error)?;
+
+    if exponent < -4 || exponent >= precision as i32 {
+        buffer.write_str(trim_fraction(mantissa))?;
+        let sign = if exponent < 0 { '-' } else { '+' };
+        write!(buffer, "e{sign}{:02}", exponent.unsigned_abs())
+    } else {
+        let mut fixed = NumberBuffer::new();
+        let decimals = (precision as i32 - 1 - exponent) as usize;
+        write!(fixed, "{n:.decimals$}")?;
+        buffer.write_str(trim_fraction(fixed.as_str()))
+    }
+}
+
+/// Removes trailing zeros from the fractional part, like `%g` does.
+fn trim_fraction(s: &str) -> &str {
+    if s.contains('.') {
+        s.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        s
+    }
+}
+
+/// Compares two doubles the same way cJSON's `compare_double` does.
+fn approx_eq(a: f64, b: f64) -> bool {
+    let abs = |n: f64| if n.is_sign_negative() { -n } else { n };
+    abs(a - b) <= abs(a).max(abs(b)) * f64::EPSILON
+}
+
+/// A stack buffer big enough for any number printed by cJSON.
+pub(super) struct NumberBuffer {
+    bytes: [u8; 32],
+    len: usize,
+}
+
+impl NumberBuffer {
+    pub(super) fn new() -> Self {
+        Self {
+            bytes: [0; 32],
+            len: 0,
+        }
+    }
+
+    fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    pub(super) fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+
+    fn as_str(&self) -> &str {
+        // Only `write_str` fills the buffer, so it always holds valid UTF-8.
+        core::str::from_utf8(self.as_bytes()).unwrap_or_default()
+    }
+}
+
+impl fmt::Write for NumberBuffer {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        self.bytes
+            .get_mut(self.len..end)
+            .ok_or(fmt::Error)?
+            .copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
@@ -2,24 +2,30 @@
 extern crate alloc;
 
 #[cfg(not(feature = "std"))]
-use alloc::{borrow::ToOwned, ffi::CString, string::ToString};
+use alloc::{ffi::CString, string::String, string::ToString};
 
 #[cfg(feature = "std")]
 use std::ffi::CString;
 
+use super::raw;
 use super::Error;
 use crate::CJson;
 use crate::CJsonArray;
+use crate::CJsonIter;
 use crate::CJsonObject;
 use crate::CJsonRef;
+use crate::ParseOptions;
 use core::fmt::Display;
+use serde::de::Error as _;
 use serde::de::*;
 
 /// Deserialize a value from a CJson value.
+///
+/// Strings are borrowed from the given value whenever the target type allows
+/// it (e.g. `&str` fields), so no allocation takes place for them.
 #[inline(always)]
-pub fn from_cjson<'de, T: DeserializeOwned>(cjson: &'_ CJsonRef<'_>) -> Result<T, Error> {
-    todo!("from_cjson")
-    // T::deserialize(Deserializer(cjson))
+pub fn from_cjson<'de, T: Deserialize<'de>>(cjson: &'de CJsonRef<'de>) -> super::Result<T> {
+    T::deserialize(Deserializer(cjson))
 }
 
 impl serde::de::Error for Error {
@@ -31,155 +37,796 @@ impl serde::de::Error for Error {
     }
 }
 
+/// A deserializer for CJson values.
+///
+/// Raw values are parsed on demand, so they can be deserialized into any type,
+/// but strings cannot be borrowed from them.
+#[derive(Debug, Clone, Copy)]
 pub struct Deserializer<'de>(pub &'de CJsonRef<'de>);
 
-/*
-// Deserialization
+impl Deserializer<'_> {
+    /// Parses the fragment held by a raw value.
+    fn parse_raw(self) -> super::Result<Option<CJson<'static>>> {
+        let Some(fragment) = self.0.as_raw() else {
+            return Ok(None);
+        };
+        let (parsed, _) = ParseOptions::new()
+            .require_end_of_input(true)
+            .parse(fragment.to_bytes())?;
+        Ok(Some(parsed))
+    }
+}
 
+impl<'de> serde::Deserializer<'de> for Deserializer<'de> {
+    type Error = Error;
 
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let cjson = self.0;
+        if cjson.is_null() {
+            visitor.visit_unit()
+        } else if let Some(b) = cjson.as_bool() {
+            visitor.visit_bool(b)
+        } else if let Some(n) = cjson.as_number() {
+            visit_number(n, visitor)
+        } else if let Some(n) = cjson.as_i64() {
+            visitor.visit_i64(n)
+        } else if let Some(n) = cjson.as_u64() {
+            visitor.visit_u64(n)
+        } else if let Some(s) = cjson.as_c_string() {
+            visitor.visit_borrowed_str(s.to_str()?)
+        } else if cjson.is_array() {
+            visitor.visit_seq(SeqDeserializer::new(cjson))
+        } else if cjson.is_object() {
+            visitor.visit_map(MapDeserializer::new(cjson))
+        } else if let Some(parsed) = self.parse_raw()? {
+            OwnedDeserializer(&parsed).deserialize_any(visitor)
+        } else {
+            Err(Error::custom("Malformed cJSON"))
+        }
+    }
 
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if self.0.is_null() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
 
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if self.0.is_null() {
+            visitor.visit_unit()
+        } else if let Some(parsed) = self.parse_raw()? {
+            OwnedDeserializer(&parsed).deserialize_unit(visitor)
+        } else {
+            Err(Error::invalid_type(unexpected(self.0)?, &visitor))
+        }
+    }
 
-impl<'de> Deserialize<'de> for CJson {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    fn deserialize_unit_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
     where
-        D: serde::Deserializer<'de>,
+        V: Visitor<'de>,
     {
-        struct CJsonVisitor;
+        self.deserialize_unit(visitor)
+    }
 
-        impl<'vi> Visitor<'vi> for CJsonVisitor {
-            type Value = CJson;
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if name != raw::TOKEN {
+            visitor.visit_newtype_struct(self)
+        } else if let Some(fragment) = self.0.as_raw() {
+            visitor.visit_borrowed_str(fragment.to_str()?)
+        } else {
+            visitor.visit_string(capture_raw(self.0)?)
+        }
+    }
 
-            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-                formatter.write_str("any valid cJSON value")
-            }
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if self.0.is_array() {
+            visitor.visit_seq(SeqDeserializer::new(self.0))
+        } else if let Some(parsed) = self.parse_raw()? {
+            OwnedDeserializer(&parsed).deserialize_seq(visitor)
+        } else {
+            Err(Error::invalid_type(unexpected(self.0)?, &visitor))
+        }
+    }
 
-            fn visit_i8<E>(self, v: i8) -> Result<Self::Value, E>
-            where
-                E: serde::de::Error,
-            {
-                self.visit_f64(v.into())
-            }
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
 
-            fn visit_i16<E>(self, v: i16) -> Result<Self::Value, E>
-            where
-                E: serde::de::Error,
-            {
-                self.visit_f64(v.into())
-            }
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
 
-            fn visit_i32<E>(self, v: i32) -> Result<Self::Value, E>
-            where
-                E: serde::de::Error,
-            {
-                self.visit_f64(v.into())
-            }
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if self.0.is_object() {
+            visitor.visit_map(MapDeserializer::new(self.0))
+        } else if let Some(parsed) = self.parse_raw()? {
+            OwnedDeserializer(&parsed).deserialize_map(visitor)
+        } else {
+            Err(Error::invalid_type(unexpected(self.0)?, &visitor))
+        }
+    }
 
-            fn visit_u8<E>(self, v: u8) -> Result<Self::Value, E>
-            where
-                E: serde::de::Error,
-            {
-                self.visit_f64(v.into())
-            }
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_map(visitor)
+    }
 
-            fn visit_u16<E>(self, v: u16) -> Result<Self::Value, E>
-            where
-                E: serde::de::Error,
-            {
-                self.visit_f64(v.into())
+    /// Deserializes an externally tagged enum, as produced by
+    /// [`Serializer`](super::Serializer): unit variants are strings, and any
+    /// other variant is an object with a single key.
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let cjson = self.0;
+        if let Some(parsed) = self.parse_raw()? {
+            OwnedDeserializer(&parsed).deserialize_enum(name, variants, visitor)
+        } else if let Some(s) = cjson.as_c_string() {
+            visitor.visit_enum(value::BorrowedStrDeserializer::new(s.to_str()?))
+        } else if cjson.is_object() {
+            let mut iter = cjson.iter();
+            match (iter.next(), iter.next()) {
+                (Some(value), None) => visitor.visit_enum(EnumDeserializer(value)),
+                _ => Err(Error::invalid_value(
+                    Unexpected::Map,
+                    &"map with a single key",
+                )),
             }
+        } else {
+            Err(Error::invalid_type(unexpected(cjson)?, &"string or map"))
+        }
+    }
 
-            fn visit_u32<E>(self, v: u32) -> Result<Self::Value, E>
-            where
-                E: serde::de::Error,
-            {
-                self.visit_f64(v.into())
-            }
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
 
-            fn visit_f32<E>(self, v: f32) -> Result<Self::Value, E>
-            where
-                E: serde::de::Error,
-            {
-                self.visit_f64(v.into())
-            }
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf identifier
+    }
+}
 
-            fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E>
-            where
-                E: serde::de::Error,
-            {
-                CJson::number(v).map_err(serde::de::Error::custom)
-            }
+/// Prints a value into the fragment captured by a [`RawJson`](super::RawJson).
+///
+/// Printing fails on strings that are not valid UTF-8, which cJSON happily
+/// parses, so this must not go through [`ToString`].
+fn capture_raw(cjson: &CJsonRef<'_>) -> super::Result<String> {
+    let mut fragment = String::new();
+    cjson
+        .to_fmt_writer(&mut fragment, false)
+        .map_err(|_| Error::custom("value cannot be captured as raw JSON"))?;
+    Ok(fragment)
+}
 
-            fn visit_bool<E>(self, v: bool) -> Result<Self::Value, E>
-            where
-                E: serde::de::Error,
-            {
-                CJson::bool(v).map_err(serde::de::Error::custom)
-            }
+/// Describes a cJSON value for error reporting.
+fn unexpected<'de>(cjson: &'de CJsonRef<'_>) -> super::Result<Unexpected<'de>> {
+    let unexpected = if cjson.is_null() {
+        Unexpected::Unit
+    } else if let Some(b) = cjson.as_bool() {
+        Unexpected::Bool(b)
+    } else if let Some(n) = cjson.as_number() {
+        Unexpected::Float(n)
+    } else if let Some(n) = cjson.as_i64() {
+        Unexpected::Signed(n)
+    } else if let Some(n) = cjson.as_u64() {
+        Unexpected::Unsigned(n)
+    } else if let Some(s) = cjson.as_c_string() {
+        Unexpected::Str(s.to_str()?)
+    } else if cjson.is_array() {
+        Unexpected::Seq
+    } else if cjson.is_object() {
+        Unexpected::Map
+    } else if cjson.is_raw() {
+        Unexpected::Other("raw JSON")
+    } else {
+        Unexpected::Other("malformed cJSON")
+    };
+    Ok(unexpected)
+}
 
-            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
-            where
-                E: serde::de::Error,
-            {
-                let v = CString::new(v).map_err(serde::de::Error::custom)?;
-                CJson::string(v).map_err(serde::de::Error::custom)
-            }
+/// Visits a cJSON number using the narrowest lossless representation.
+///
+/// cJSON stores every number as a double, so integral values are handed to the
+/// visitor as integers. Otherwise, integer fields could not be deserialized.
+fn visit_number<'de, V>(n: f64, visitor: V) -> Result<V::Value, Error>
+where
+    V: Visitor<'de>,
+{
+    if n >= 0.0 && n < u64::MAX as f64 && n == n as u64 as f64 {
+        visitor.visit_u64(n as u64)
+    } else if n < 0.0 && n >= i64::MIN as f64 && n == n as i64 as f64 {
+        visitor.visit_i64(n as i64)
+    } else {
+        visitor.visit_f64(n)
+    }
+}
 
-            fn visit_none<E>(self) -> Result<Self::Value, E>
-            where
-                E: serde::de::Error,
-            {
-                self.visit_unit()
-            }
+struct SeqDeserializer<'de> {
+    iter: CJsonIter<'de, 'de>,
+    remaining: usize,
+}
 
-            fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
-            where
-                D: serde::Deserializer<'vi>,
-            {
-                Deserialize::deserialize(deserializer)
+impl<'de> SeqDeserializer<'de> {
+    fn new(cjson: &'de CJsonRef<'de>) -> Self {
+        SeqDeserializer {
+            iter: cjson.iter(),
+            remaining: cjson.len() as usize,
+        }
+    }
+}
+
+impl<'de> SeqAccess<'de> for SeqDeserializer<'de> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        match self.iter.next() {
+            Some(value) => {
+                self.remaining -= 1;
+                seed.deserialize(Deserializer(value)).map(Some)
             }
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+struct MapDeserializer<'de> {
+    iter: CJsonIter<'de, 'de>,
+    value: Option<&'de CJsonRef<'de>>,
+    remaining: usize,
+}
+
+impl<'de> MapDeserializer<'de> {
+    fn new(cjson: &'de CJsonRef<'de>) -> Self {
+        MapDeserializer {
+            iter: cjson.iter(),
+            value: None,
+            remaining: cjson.len() as usize,
+        }
+    }
+}
 
-            fn visit_unit<E>(self) -> Result<Self::Value, E>
+impl<'de> MapAccess<'de> for MapDeserializer<'de> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        let Some(item) = self.iter.next() else {
+            return Ok(None);
+        };
+        self.remaining -= 1;
+        self.value = Some(item);
+
+        let key = item
+            .name()
+            .ok_or_else(|| Error::custom("Object member without a key"))?
+            .to_str()?;
+        seed.deserialize(MapKeyDeserializer(key)).map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let value = self
+            .value
+            .take()
+            .expect("next_value_seed called before next_key_seed");
+        seed.deserialize(Deserializer(value))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+/// Deserializes object keys.
+///
+/// Keys are always strings, but [`Serializer`](super::Serializer) also accepts
+/// numbers and booleans as keys, so those are parsed back from their string
+/// representation.
+struct MapKeyDeserializer<'de>(&'de str);
+
+macro_rules! deserialize_parsed_key {
+    ($($method:ident => $visit:ident),* $(,)?) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
             where
-                E: serde::de::Error,
+                V: Visitor<'de>,
             {
-                CJson::null().map_err(serde::de::Error::custom)
+                match self.0.parse() {
+                    Ok(v) => visitor.$visit(v),
+                    Err(_) => Err(Error::invalid_value(Unexpected::Str(self.0), &visitor)),
+                }
             }
+        )*
+    };
+}
 
-            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
-            where
-                A: serde::de::SeqAccess<'vi>,
-            {
-                let mut array = CJsonArray::new().map_err(serde::de::Error::custom)?;
+impl<'de> serde::Deserializer<'de> for MapKeyDeserializer<'de> {
+    type Error = Error;
 
-                while let Some(v) = seq.next_element::<CJson>()? {
-                    array.push(v);
-                }
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_borrowed_str(self.0)
+    }
 
-                Ok(array.into())
-            }
+    deserialize_parsed_key! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+    }
 
-            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
-            where
-                A: serde::de::MapAccess<'vi>,
-            {
-                let mut obj = CJsonObject::new().map_err(serde::de::Error::custom)?;
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
 
-                while let Some((k, v)) = map.next_entry()? {
-                    let k: String = k;
-                    let v: CJson = v;
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
 
-                    let k = CString::new(k).map_err(serde::de::Error::custom)?;
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_enum(value::BorrowedStrDeserializer::new(self.0))
+    }
 
-                    obj.insert(k, v);
-                }
-                Ok(obj.into())
-            }
+    serde::forward_to_deserialize_any! {
+        char str string bytes byte_buf unit unit_struct seq tuple tuple_struct
+        map struct identifier ignored_any
+    }
+}
+
+/// Accesses the single key-value pair of an externally tagged enum.
+struct EnumDeserializer<'de>(&'de CJsonRef<'de>);
+
+impl<'de> EnumAccess<'de> for EnumDeserializer<'de> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let variant = self
+            .0
+            .name()
+            .ok_or_else(|| Error::custom("Object member without a key"))?
+            .to_str()?;
+        let variant = seed.deserialize(MapKeyDeserializer(variant))?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> VariantAccess<'de> for EnumDeserializer<'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        Deserialize::deserialize(Deserializer(self.0))
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        seed.deserialize(Deserializer(self.0))
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        serde::Deserializer::deserialize_seq(Deserializer(self.0), visitor)
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        serde::Deserializer::deserialize_map(Deserializer(self.0), visitor)
+    }
+}
+
+/// Deserializes a tree parsed from a raw value.
+///
+/// The tree is dropped once deserialization is done, so strings are handed to
+/// visitors as copies rather than borrowed.
+struct OwnedDeserializer<'a>(&'a CJsonRef<'a>);
+
+impl<'de> serde::Deserializer<'de> for OwnedDeserializer<'_> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let cjson = self.0;
+        if cjson.is_null() {
+            visitor.visit_unit()
+        } else if let Some(b) = cjson.as_bool() {
+            visitor.visit_bool(b)
+        } else if let Some(n) = cjson.as_number() {
+            visit_number(n, visitor)
+        } else if let Some(s) = cjson.as_c_string() {
+            visitor.visit_str(s.to_str()?)
+        } else if cjson.is_array() {
+            visitor.visit_seq(OwnedSeqAccess(cjson.iter()))
+        } else if cjson.is_object() {
+            visitor.visit_map(OwnedMapAccess {
+                iter: cjson.iter(),
+                value: None,
+            })
+        } else {
+            Err(Error::custom("Malformed cJSON"))
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if self.0.is_null() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if name == raw::TOKEN {
+            visitor.visit_string(capture_raw(self.0)?)
+        } else {
+            visitor.visit_newtype_struct(self)
+        }
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let cjson = self.0;
+        if let Some(s) = cjson.as_c_string() {
+            visitor.visit_enum(value::StrDeserializer::new(s.to_str()?))
+        } else if cjson.is_object() && cjson.len() == 1 {
+            visitor.visit_enum(value::MapAccessDeserializer::new(OwnedMapAccess {
+                iter: cjson.iter(),
+                value: None,
+            }))
+        } else if cjson.is_object() {
+            Err(Error::invalid_value(
+                Unexpected::Map,
+                &"map with a single key",
+            ))
+        } else {
+            Err(Error::invalid_type(unexpected(cjson)?, &"string or map"))
         }
+    }
 
-        deserializer.deserialize_any(CJsonVisitor)
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
     }
 }
 
+struct OwnedSeqAccess<'a>(CJsonIter<'a, 'a>);
+
+impl<'de> SeqAccess<'de> for OwnedSeqAccess<'_> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.0
+            .next()
+            .map(|value| seed.deserialize(OwnedDeserializer(value)))
+            .transpose()
+    }
+}
 
- */
+struct OwnedMapAccess<'a> {
+    iter: CJsonIter<'a, 'a>,
+    value: Option<&'a CJsonRef<'a>>,
+}
+
+impl<'de> MapAccess<'de> for OwnedMapAccess<'_> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        let Some(item) = self.iter.next() else {
+            return Ok(None);
+        };
+        self.value = Some(item);
+
+        let key = item
+            .name()
+            .ok_or_else(|| Error::custom("Object member without a key"))?
+            .to_str()?;
+        seed.deserialize(value::StrDeserializer::new(key)).map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let value = self
+            .value
+            .take()
+            .expect("next_value_seed called before next_key_seed");
+        seed.deserialize(OwnedDeserializer(value))
+    }
+}
+
+impl<'de, 'json> Deserialize<'de> for CJson<'json> {
+    /// Deserializes any value into a CJson instance.
+    ///
+    /// Integers that doubles cannot represent exactly are rejected, use
+    /// [`LosslessSeed`] to keep them as raw number tokens instead.
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(CJsonVisitor { lossless: false })
+    }
+}
+
+/// A [`DeserializeSeed`] that deserializes any value into a CJson instance,
+/// storing integers that doubles cannot represent exactly as raw number
+/// tokens, see [`CJson::i64_lossless`].
+///
+/// # Example usage
+///
+/// ```
+/// use cjsonrs::serde::{to_cjson_lossless, Deserializer, LosslessSeed};
+/// use cjsonrs::CJson;
+/// use serde::de::DeserializeSeed;
+///
+/// let cjson = to_cjson_lossless(&[u64::MAX])?;
+/// assert!(cjsonrs::serde::from_cjson::<CJson>(&cjson).is_err());
+///
+/// let copy = LosslessSeed.deserialize(Deserializer(&cjson))?;
+/// assert_eq!(copy.to_string(), "[18446744073709551615]");
+/// # Ok::<(), cjsonrs::serde::Error>(())
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LosslessSeed;
+
+impl<'de> DeserializeSeed<'de> for LosslessSeed {
+    type Value = CJson<'static>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(CJsonVisitor { lossless: true })
+    }
+}
+
+#[derive(Clone, Copy)]
+struct CJsonVisitor {
+    lossless: bool,
+}
+
+impl<'de> DeserializeSeed<'de> for CJsonVisitor {
+    type Value = CJson<'static>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'vi> Visitor<'vi> for CJsonVisitor {
+    type Value = CJson<'static>;
+
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+        formatter.write_str("any valid cJSON value")
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        if self.lossless {
+            CJson::i64_lossless(v).map_err(serde::de::Error::custom)
+        } else {
+            CJson::i64(v).map_err(serde::de::Error::custom)
+        }
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        if self.lossless {
+            CJson::u64_lossless(v).map_err(serde::de::Error::custom)
+        } else {
+            CJson::u64(v).map_err(serde::de::Error::custom)
+        }
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        CJson::number(v).map_err(serde::de::Error::custom)
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        CJson::bool(v).map_err(serde::de::Error::custom)
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        let v = CString::new(v).map_err(serde::de::Error::custom)?;
+        CJson::string(v).map_err(serde::de::Error::custom)
+    }
+
+    fn visit_none<E>(self) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        self.visit_unit()
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'vi>,
+    {
+        deserializer.deserialize_any(self)
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        CJson::null().map_err(serde::de::Error::custom)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::SeqAccess<'vi>,
+    {
+        let mut array = CJsonArray::new().map_err(serde::de::Error::custom)?;
+
+        while let Some(v) = seq.next_element_seed(self)? {
+            array.push(v);
+        }
+
+        Ok(array.into())
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::MapAccess<'vi>,
+    {
+        let mut obj = CJsonObject::new().map_err(serde::de::Error::custom)?;
+
+        while let Some(k) = map.next_key::<String>()? {
+            let v = map.next_value_seed(self)?;
+
+            let k = CString::new(k).map_err(serde::de::Error::custom)?;
+
+            obj.insert(k, v);
+        }
+        Ok(obj.into())
+    }
+}
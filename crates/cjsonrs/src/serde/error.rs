@@ -7,6 +7,8 @@ use alloc::{ffi::NulError, string::String};
 #[cfg(feature = "std")]
 use std::ffi::NulError;
 
+use core::str::Utf8Error;
+
 /// Error type for serde operations
 #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
 pub enum Error {
@@ -18,6 +20,8 @@ pub enum Error {
     CJson(#[from] crate::Error),
     #[error("Failed to construct CString from UTF-8 string: {0}")]
     CString(#[from] NulError),
+    #[error("cJSON string is not valid UTF-8: {0}")]
+    Utf8(#[from] Utf8Error),
     #[error("{0}")]
     Custom(String),
 }
@@ -37,8 +37,10 @@
 
 mod de;
 mod error;
+mod raw;
 mod ser;
 
-pub use de::{from_cjson, Deserializer};
+pub use de::{from_cjson, Deserializer, LosslessSeed};
 pub use error::{Error, Result};
-pub use ser::{to_cjson, Serializer};
+pub use raw::RawJson;
+pub use ser::{to_cjson, to_cjson_lossless, Serializer};
@@ -0,0 +1,146 @@
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+
+#[cfg(not(feature = "std"))]
+use alloc::{borrow::ToOwned, string::String};
+
+use crate::ParseOptions;
+use core::fmt::Display;
+use serde::de::{Deserialize, Visitor};
+use serde::ser::Serialize;
+
+/// Name of the newtype struct through which [`RawJson`] fragments are handed
+/// to the [`Serializer`](super::Serializer) and taken from the
+/// [`Deserializer`](super::Deserializer).
+pub(super) const TOKEN: &str = "$cjsonrs::private::RawJson";
+
+/// A pre-serialized JSON fragment, similar to `serde_json::value::RawValue`.
+///
+/// [`to_cjson`](super::to_cjson) turns it into a raw value, which is printed
+/// verbatim, so cached payloads can be spliced into larger messages without
+/// parsing them again. [`from_cjson`](super::from_cjson) captures the compact
+/// serialization of any value into it.
+///
+/// Other serializers see the fragment as a string.
+///
+/// # Example usage
+///
+/// ```
+/// use cjsonrs::serde::{from_cjson, to_cjson, RawJson};
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Message {
+///     id: u32,
+///     reading: RawJson,
+/// }
+///
+/// let reading = RawJson::from_string(r#"{"temperature": 21.5}"#.to_owned())?;
+/// let cjson = to_cjson(&Message { id: 1, reading })?;
+/// assert_eq!(cjson.to_string(), r#"{"id":1,"reading":{"temperature": 21.5}}"#);
+///
+/// let parsed: cjsonrs::CJson = r#"{"id": 2, "reading": [1, 2]}"#.parse()?;
+/// let message: Message = from_cjson(&parsed)?;
+/// assert_eq!(message.reading.get(), "[1,2]");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RawJson {
+    json: String,
+}
+
+impl RawJson {
+    /// Wraps a JSON fragment, checking that it is a single valid JSON value.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the fragment contains a nul byte or
+    /// is not valid JSON.
+    pub fn from_string(json: String) -> super::Result<Self> {
+        if let Some(position) = json.bytes().position(|b| b == 0) {
+            return Err(crate::Error::InteriorNul(position).into());
+        }
+        ParseOptions::new()
+            .require_end_of_input(true)
+            .parse(json.as_bytes())?;
+        Ok(Self { json })
+    }
+
+    /// Wraps a JSON fragment without checking it.
+    ///
+    /// The fragment is printed verbatim, so an invalid fragment results in
+    /// invalid documents.
+    #[inline(always)]
+    pub fn from_string_unvalidated(json: String) -> Self {
+        Self { json }
+    }
+
+    /// Returns the JSON fragment.
+    #[inline(always)]
+    pub fn get(&self) -> &str {
+        &self.json
+    }
+
+    /// Consumes the [`RawJson`] and returns the JSON fragment.
+    #[inline(always)]
+    pub fn into_string(self) -> String {
+        self.json
+    }
+}
+
+impl Display for RawJson {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.json)
+    }
+}
+
+impl Serialize for RawJson {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_newtype_struct(TOKEN, self.get())
+    }
+}
+
+impl<'de> Deserialize<'de> for RawJson {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct RawJsonVisitor;
+
+        impl<'de> Visitor<'de> for RawJsonVisitor {
+            type Value = RawJson;
+
+            fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+                formatter.write_str("a JSON fragment")
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(RawJson::from_string_unvalidated(v.to_owned()))
+            }
+
+            fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(RawJson::from_string_unvalidated(v))
+            }
+
+            // Deserializers that do not know the token hand the newtype
+            // over as is, in which case the fragment is read as a string.
+            fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                String::deserialize(deserializer).map(RawJson::from_string_unvalidated)
+            }
+        }
+
+        deserializer.deserialize_newtype_struct(TOKEN, RawJsonVisitor)
+    }
+}
@@ -7,6 +7,7 @@ use alloc::{borrow::ToOwned, ffi::CString, format, string::ToString};
 #[cfg(feature = "std")]
 use std::ffi::CString;
 
+use super::raw;
 use super::Error;
 use crate::CJson;
 use crate::CJsonArray;
@@ -18,9 +19,21 @@ use serde::ser::*;
 // Based on https://github.com/serde-rs/json/blob/master/src/value/ser.rs
 
 /// Serializes a value to a CJson instance.
+///
+/// Integers that doubles cannot represent exactly are rejected, see
+/// [`Serializer`] for more information.
 #[inline(always)]
-pub fn to_cjson<T: Serialize>(value: T) -> super::Result<CJson<'static>> {
-    value.serialize(Serializer)
+pub fn to_cjson<T: ?Sized + Serialize>(value: &T) -> super::Result<CJson<'static>> {
+    value.serialize(Serializer::new())
+}
+
+/// Serializes a value to a CJson instance, storing integers that doubles
+/// cannot represent exactly as raw number tokens.
+///
+/// See [`Serializer::lossless`] for more information.
+#[inline(always)]
+pub fn to_cjson_lossless<T: ?Sized + Serialize>(value: &T) -> super::Result<CJson<'static>> {
+    value.serialize(Serializer::lossless())
 }
 
 impl serde::ser::Error for Error {
@@ -74,6 +87,10 @@ impl Serialize for CJsonRef<'_> {
             serializer.serialize_bool(b)
         } else if let Some(n) = self.as_number() {
             serializer.serialize_f64(n)
+        } else if let Some(n) = self.as_i64() {
+            serializer.serialize_i64(n)
+        } else if let Some(n) = self.as_u64() {
+            serializer.serialize_u64(n)
         } else if let Some(s) = self.as_c_string() {
             let s = s.to_str().map_err(serde::ser::Error::custom)?;
             serializer.serialize_str(s)
@@ -81,6 +98,9 @@ impl Serialize for CJsonRef<'_> {
             a.serialize(serializer)
         } else if let Some(o) = self.as_object() {
             o.serialize(serializer)
+        } else if let Some(raw) = self.as_raw() {
+            let raw = raw.to_str().map_err(serde::ser::Error::custom)?;
+            serializer.serialize_newtype_struct(raw::TOKEN, raw)
         } else {
             unreachable!("Malformed cJSON")
         }
@@ -88,8 +108,42 @@ impl Serialize for CJsonRef<'_> {
 }
 
 /// A serializer for CJson values.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct Serializer;
+///
+/// cJSON stores numbers as doubles, so by default `i64` and `u64` values
+/// above 2^53 that doubles cannot represent exactly are rejected with
+/// [`Error::InexactNumber`](crate::Error::InexactNumber).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Serializer {
+    lossless: bool,
+}
+
+impl Serializer {
+    /// Creates a serializer that rejects inexact integers.
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self { lossless: false }
+    }
+
+    /// Creates a serializer that stores integers that doubles cannot
+    /// represent exactly as raw number tokens, see [`CJson::i64_lossless`].
+    ///
+    /// Raw tokens round-trip bit-exact, but they are not numbers to cJSON
+    /// (e.g. [`CJsonRef::as_number`] returns [`None`] for them).
+    ///
+    /// # Example usage
+    ///
+    /// ```
+    /// use cjsonrs::serde::{to_cjson, to_cjson_lossless};
+    ///
+    /// assert!(to_cjson(&u64::MAX).is_err());
+    /// assert_eq!(to_cjson_lossless(&u64::MAX)?.to_string(), "18446744073709551615");
+    /// # Ok::<(), cjsonrs::serde::Error>(())
+    /// ```
+    #[inline(always)]
+    pub const fn lossless() -> Self {
+        Self { lossless: true }
+    }
+}
 
 impl serde::Serializer for Serializer {
     type Ok = CJson<'static>;
@@ -122,7 +176,12 @@ impl serde::Serializer for Serializer {
     }
 
     fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
-        self.serialize_f64(v as _)
+        let cjson = if self.lossless {
+            CJson::i64_lossless(v)?
+        } else {
+            CJson::i64(v)?
+        };
+        Ok(cjson)
     }
 
     fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
@@ -138,7 +197,12 @@ impl serde::Serializer for Serializer {
     }
 
     fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
-        self.serialize_f64(v as _)
+        let cjson = if self.lossless {
+            CJson::u64_lossless(v)?
+        } else {
+            CJson::u64(v)?
+        };
+        Ok(cjson)
     }
 
     fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
@@ -199,13 +263,21 @@ impl serde::Serializer for Serializer {
 
     fn serialize_newtype_struct<T>(
         self,
-        _name: &'static str,
+        name: &'static str,
         value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
         T: ?Sized + Serialize,
     {
-        value.serialize(self)
+        if name != raw::TOKEN {
+            return value.serialize(self);
+        }
+
+        // Raw fragments are handed over as strings, which are copied straight
+        // into the raw value.
+        let fragment = value.serialize(MapKeySerializer)?;
+        let cjson = CJson::raw_unvalidated(fragment)?;
+        Ok(cjson)
     }
 
     fn serialize_newtype_variant<T>(
@@ -226,7 +298,10 @@ impl serde::Serializer for Serializer {
 
     fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
         let array = CJsonArray::new()?;
-        Ok(SerializeCJsonArray { array })
+        Ok(SerializeCJsonArray {
+            array,
+            serializer: self,
+        })
     }
 
     fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
@@ -251,6 +326,7 @@ impl serde::Serializer for Serializer {
         Ok(SerializeTupleVariant {
             name: CString::new(variant)?,
             array: CJsonArray::new()?,
+            serializer: self,
         })
     }
 
@@ -258,6 +334,7 @@ impl serde::Serializer for Serializer {
         Ok(SerializeCJsonObject {
             object: CJsonObject::new()?,
             next_key: None,
+            serializer: self,
         })
     }
 
@@ -279,6 +356,7 @@ impl serde::Serializer for Serializer {
         Ok(SerializeStructVariant {
             name: CString::new(variant)?,
             object: CJsonObject::new()?,
+            serializer: self,
         })
     }
     fn collect_str<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
@@ -294,6 +372,7 @@ impl serde::Serializer for Serializer {
 
 pub struct SerializeCJsonArray {
     array: CJsonArray<CJson<'static>>,
+    serializer: Serializer,
 }
 
 impl SerializeSeq for SerializeCJsonArray {
@@ -304,7 +383,7 @@ impl SerializeSeq for SerializeCJsonArray {
     where
         T: ?Sized + Serialize,
     {
-        let value = to_cjson(value)?;
+        let value = value.serialize(self.serializer)?;
         self.array.push(value);
         Ok(())
     }
@@ -349,6 +428,7 @@ impl SerializeTupleStruct for SerializeCJsonArray {
 pub struct SerializeCJsonObject {
     object: CJsonObject<CJson<'static>>,
     next_key: Option<CString>,
+    serializer: Serializer,
 }
 
 impl SerializeMap for SerializeCJsonObject {
@@ -371,7 +451,7 @@ impl SerializeMap for SerializeCJsonObject {
             .next_key
             .take()
             .expect("serialize_value called before serialize_key");
-        let value = to_cjson(value)?;
+        let value = value.serialize(self.serializer)?;
         self.object.insert(key, value);
         Ok(())
     }
@@ -400,6 +480,7 @@ impl SerializeStruct for SerializeCJsonObject {
 pub struct SerializeTupleVariant {
     name: CString,
     array: CJsonArray<CJson<'static>>,
+    serializer: Serializer,
 }
 
 impl serde::ser::SerializeTupleVariant for SerializeTupleVariant {
@@ -410,12 +491,12 @@ impl serde::ser::SerializeTupleVariant for SerializeTupleVariant {
     where
         T: ?Sized + Serialize,
     {
-        self.array.push(to_cjson(value)?);
+        self.array.push(value.serialize(self.serializer)?);
         Ok(())
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        let SerializeTupleVariant { name, array } = self;
+        let SerializeTupleVariant { name, array, .. } = self;
         Ok(cjson!({name => array})?.into())
     }
 }
@@ -423,6 +504,7 @@ impl serde::ser::SerializeTupleVariant for SerializeTupleVariant {
 pub struct SerializeStructVariant {
     name: CString,
     object: CJsonObject<CJson<'static>>,
+    serializer: Serializer,
 }
 
 impl serde::ser::SerializeStructVariant for SerializeStructVariant {
@@ -433,12 +515,13 @@ impl serde::ser::SerializeStructVariant for SerializeStructVariant {
     where
         T: ?Sized + Serialize,
     {
-        self.object.insert(CString::new(key)?, to_cjson(value)?);
+        self.object
+            .insert(CString::new(key)?, value.serialize(self.serializer)?);
         Ok(())
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        let SerializeStructVariant { name, object } = self;
+        let SerializeStructVariant { name, object, .. } = self;
         Ok(cjson!({name => object})?.into())
     }
 }
@@ -8,6 +8,9 @@ use core::hash::Hash;
 use core::ops::Deref;
 use core::ptr::NonNull;
 
+use super::hooks::Usage;
+use super::Error;
+
 /// An owned, null-terminated, C-style string.
 ///
 /// This type is used to represent strings that are owned by the caller, but were allocated by the
@@ -31,17 +34,52 @@ impl CJsonString {
     ///
     /// - `ptr` must be a valid pointer to a null-terminated C-style string.
     /// - `ptr` must be allocated by the cJSON library. (i.e. it must be freed using [`cjsonrs_sys::cJSON_free`].)
+    /// - `ptr` must be allocated with the current [hooks](super::hooks).
     /// - `len` must be the length of the string, including the null terminator.
     ///
     pub unsafe fn from_raw_parts(ptr: NonNull<c_char>, len: usize) -> Self {
+        Usage::acquire().forget();
         Self { ptr, len }
     }
+
+    /// Copies `s` into a new C string allocated by the cJSON library.
+    ///
+    /// # Errors
+    ///
+    /// This function returns [`Error::InteriorNul`] if `s` contains a nul
+    /// byte, or an error if the allocation fails.
+    pub(super) fn copy_from_str(s: &str) -> core::result::Result<Self, Error> {
+        if let Some(position) = s.bytes().position(|b| b == 0) {
+            return Err(Error::InteriorNul(position));
+        }
+
+        let _usage = Usage::acquire();
+        let len = s.len() + 1;
+        let ptr = unsafe { cjsonrs_sys::cJSON_malloc(len) }.cast::<c_char>();
+        let ptr = NonNull::new(ptr).ok_or(Error::Allocation)?;
+        unsafe {
+            core::ptr::copy_nonoverlapping(s.as_ptr(), ptr.as_ptr().cast(), s.len());
+            ptr.as_ptr().add(s.len()).write(0);
+            Ok(Self::from_raw_parts(ptr, len))
+        }
+    }
+
+    /// Consumes the [`CJsonString`] and returns the underlying pointer. The
+    /// callee takes ownership of the allocation, e.g. by storing it in a cJSON
+    /// node, and must ensure that it is rightfully deallocated.
+    pub(super) fn into_raw(self) -> NonNull<c_char> {
+        let ptr = self.ptr;
+        core::mem::forget(self);
+        unsafe { Usage::release() };
+        ptr
+    }
 }
 
 impl Drop for CJsonString {
     fn drop(&mut self) {
         unsafe {
             cjsonrs_sys::cJSON_free(self.ptr.as_ptr() as *mut core::ffi::c_void);
+            Usage::release();
         }
     }
 }
@@ -0,0 +1,68 @@
+#![cfg(feature = "std")]
+
+use core::ffi::c_void;
+
+use cjsonrs::hooks::Allocator;
+use cjsonrs::hooks::Counting;
+use cjsonrs::Arena;
+use cjsonrs::CJson;
+use cjsonrs::Error;
+
+extern "C" {
+    fn malloc(size: usize) -> *mut c_void;
+    fn free(ptr: *mut c_void);
+}
+
+struct Libc;
+
+unsafe impl Allocator for Libc {
+    fn malloc(&self, size: usize) -> *mut c_void {
+        unsafe { malloc(size) }
+    }
+
+    unsafe fn free(&self, ptr: *mut c_void) {
+        free(ptr)
+    }
+}
+
+static COUNTING: Counting<Libc> = Counting::new(Libc);
+
+// Hooks are process-wide, so every step runs within a single test.
+#[test]
+fn assert_that_arenas_parse_without_allocating() -> Result<(), Box<dyn std::error::Error>> {
+    let mut buffer = vec![0; 4096];
+    let mut arena = Arena::new(&mut buffer);
+    assert_eq!(arena.capacity(), 4096);
+    #[cfg(not(feature = "rust-alloc"))]
+    assert_eq!(arena.parse(b"[1]"), Err(Error::HooksRequired));
+
+    cjsonrs::hooks::install(&COUNTING)?;
+    for id in 0..100 {
+        let message = format!(r#"{{"id": {id}, "tags": ["a", "b"], "name": "device"}}"#);
+        let request = arena.parse(message.as_bytes())?;
+        assert_eq!(
+            request.pointer(c"/id").and_then(|id| id.as_number()),
+            Some(id as f64)
+        );
+        assert_eq!(
+            request
+                .pointer(c"/tags/1")
+                .and_then(|tag| tag.as_c_string()),
+            Some(c"b")
+        );
+    }
+    assert_eq!(COUNTING.stats().allocations(), 0);
+
+    // Copies are allocated as usual.
+    let copy = arena.parse(br#"{"hello": "world"}"#)?.duplicate()?;
+    assert!(COUNTING.stats().live_bytes() > 0);
+    assert_eq!(copy, r#"{"hello": "world"}"#.parse::<CJson>()?);
+
+    let payload = format!("[{}0]", "0,".repeat(1000));
+    assert_eq!(arena.parse(payload.as_bytes()), Err(Error::Allocation));
+    assert!(matches!(arena.parse(b"[1, 2"), Err(Error::Parse(_))));
+
+    drop((copy, arena));
+    assert_eq!(COUNTING.stats().live_bytes(), 0);
+    Ok(())
+}
@@ -1,8 +1,19 @@
 use std::ffi::CString;
+use std::fmt::Write;
 
 use cjsonrs::cjson;
+use cjsonrs::escape_pointer_token;
+use cjsonrs::unescape_pointer_token;
+use cjsonrs::write_json_lines;
 use cjsonrs::CJson;
+use cjsonrs::CJsonObject;
 use cjsonrs::CJsonRef;
+use cjsonrs::Error;
+use cjsonrs::JsonPatch;
+use cjsonrs::Op;
+use cjsonrs::ParseOptions;
+use cjsonrs::PatchError;
+use cjsonrs::PrettyConfig;
 
 #[test]
 fn assert_string_reference_works_with_non_static_lifetimes(
@@ -210,3 +221,928 @@ fn assert_that_inner_items_can_be_mutated() -> Result<(), Box<dyn std::error::Er
     );
     Ok(())
 }
+
+// UTF-8 strings and keys
+#[test]
+fn assert_that_strings_can_be_read_and_created_as_utf8() -> Result<(), Box<dyn std::error::Error>> {
+    let cjson = CJson::from_str_value("grüße")?;
+    assert_eq!(cjson.as_str(), Some(Ok("grüße")));
+    assert_eq!(cjson, CJson::string(c"grüße")?);
+    assert_eq!(CJson::from_str_value("a\0b"), Err(Error::InteriorNul(1)));
+
+    let invalid = CJson::string(c"\xff")?;
+    assert!(matches!(invalid.as_str(), Some(Err(_))));
+    assert_eq!(CJson::number(1.0)?.as_str(), None);
+
+    // The value owns its copy, so it can be changed in place.
+    let mut cjson = CJson::from_str_value("short")?;
+    cjson.set_string(c"a longer string")?;
+    assert_eq!(cjson.as_str(), Some(Ok("a longer string")));
+    Ok(())
+}
+
+#[test]
+fn assert_that_objects_can_be_accessed_with_utf8_keys() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cjson: CJson = r#"{"clé": 1, "other": 2}"#.parse()?;
+    let mut object = cjson.as_mut_object().unwrap();
+    assert_eq!(object.get_str("clé").and_then(|v| v.as_number()), Some(1.0));
+    assert!(object.get_str("missing").is_none());
+    assert!(object.get_str("cl\0é").is_none());
+
+    assert!(object.get_str_mut("other").is_some_and(|v| v.is_number()));
+    assert_eq!(object.insert_str("new", CJson::null()?)?, None);
+    assert_eq!(
+        object.insert_str("clé", CJson::bool(true)?)?,
+        Some(CJson::number(1.0)?)
+    );
+    assert_eq!(
+        object.insert_str("a\0b", CJson::null()?),
+        Err(Error::InteriorNul(1))
+    );
+    assert_eq!(object.remove_str("new"), Some(CJson::null()?));
+    assert_eq!(object.remove_str("new"), None);
+
+    assert_eq!(cjson.to_string(), r#"{"other":2,"clé":true}"#);
+
+    // Keys are owned by the object, so they survive their source and detaching.
+    let mut object = CJsonObject::new()?;
+    object.insert_str(&String::from("owned"), CJson::null()?)?;
+    let detached = object.remove_str("owned");
+    assert_eq!(detached, Some(CJson::null()?));
+    drop(detached);
+    object.insert_str("owned", CJson::bool(false)?)?;
+    assert_eq!(object.to_string(), r#"{"owned":false}"#);
+    Ok(())
+}
+
+// In-place mutation
+#[test]
+fn assert_that_scalars_can_be_set_in_place() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cjson: CJson = r#"{"a": 1.5, "b": null, "c": true, "d": [], "e": "text"}"#.parse()?;
+    let mut object = cjson.as_mut_object().unwrap();
+
+    object[c"a"].set_number(3e9)?;
+    assert_eq!(object[c"a"].as_number(), Some(3e9));
+    assert_eq!(object[c"a"].as_i32(), None);
+    object[c"b"].set_bool(false)?;
+    assert_eq!(object[c"b"].as_bool(), Some(false));
+    object[c"c"].set_null()?;
+    assert!(object[c"c"].is_null());
+    object[c"c"].set_number(-2)?;
+    assert_eq!(object[c"c"].as_i32(), Some(-2));
+
+    assert_eq!(object[c"d"].set_number(1.0), Err(Error::TypeError));
+    assert_eq!(object[c"d"].set_bool(true), Err(Error::TypeError));
+    assert_eq!(object[c"e"].set_null(), Err(Error::TypeError));
+    assert_eq!(object[c"a"].set_string(c"text"), Err(Error::TypeError));
+
+    assert_eq!(
+        cjson.to_string(),
+        r#"{"a":3000000000,"b":false,"c":-2,"d":[],"e":"text"}"#
+    );
+    Ok(())
+}
+
+#[test]
+fn assert_that_strings_can_be_set_in_place() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cjson: CJson = r#"{"short": "abcdef", "long": "a"}"#.parse()?;
+    let mut object = cjson.as_mut_object().unwrap();
+    object[c"short"].set_string(c"xy")?;
+    object[c"long"].set_string(c"a much longer string")?;
+    assert_eq!(
+        cjson.to_string(),
+        r#"{"short":"xy","long":"a much longer string"}"#
+    );
+
+    let status = CString::new("idle")?;
+    let mut cjson = CJson::string_reference(&status)?;
+    cjson.set_string(c"busy")?;
+    assert_eq!(cjson.as_c_string(), Some(c"busy"));
+    assert_eq!(status.as_c_str(), c"idle");
+
+    // The node owns the copy, so cJSON can replace it.
+    cjson.set_string(c"a much longer string")?;
+    assert_eq!(cjson.to_string(), r#""a much longer string""#);
+    Ok(())
+}
+
+// Parse errors
+#[test]
+fn assert_that_parse_errors_report_their_position() -> Result<(), Box<dyn std::error::Error>> {
+    let error = "[1, x]".parse::<CJson>().unwrap_err();
+    let Error::Parse(position) = error else {
+        panic!("expected a parse error, got {error:?}");
+    };
+
+    assert_eq!(position.offset(), 4);
+    assert_eq!(position.line(), 1);
+    assert_eq!(position.column(), 5);
+    Ok(())
+}
+
+#[test]
+fn assert_that_parse_errors_report_lines_and_columns() -> Result<(), Box<dyn std::error::Error>> {
+    let error = CJson::from_c_str(c"{\n  \"a\": 1,\n  \"b\": tru\n}").unwrap_err();
+    let Error::Parse(position) = error else {
+        panic!("expected a parse error, got {error:?}");
+    };
+
+    assert_eq!(position.offset(), 19);
+    assert_eq!(position.line(), 3);
+    assert_eq!(position.column(), 8);
+    Ok(())
+}
+
+// Parse options
+#[test]
+fn assert_that_trailing_input_is_returned() -> Result<(), Box<dyn std::error::Error>> {
+    let (cjson, remainder) = ParseOptions::new().parse(br#"{"a":1} garbage"#)?;
+
+    assert_eq!(cjson, cjson!({c"a" => 1})?.into());
+    assert_eq!(remainder, b" garbage");
+    Ok(())
+}
+
+#[test]
+fn assert_that_strict_parsing_rejects_trailing_garbage() -> Result<(), Box<dyn std::error::Error>> {
+    let strict = ParseOptions::new().require_end_of_input(true);
+    let error = strict.parse(br#"{"a":1} garbage"#).unwrap_err();
+    let Error::Parse(position) = error else {
+        panic!("expected a parse error, got {error:?}");
+    };
+
+    assert_eq!(position.offset(), 8);
+    Ok(())
+}
+
+#[test]
+fn assert_that_strict_parsing_accepts_trailing_whitespace() -> Result<(), Box<dyn std::error::Error>>
+{
+    let strict = ParseOptions::new().require_end_of_input(true);
+    let (cjson, _) = strict.parse(b"[1, 2]\r\n")?;
+
+    assert_eq!(cjson, cjson!([1, 2])?.into());
+    Ok(())
+}
+
+// Multiple documents
+#[test]
+fn assert_that_newline_delimited_documents_can_be_iterated(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let input = b"{\"a\":1}\n[true]\r\n\n  42{}\n";
+    let documents = CJson::iter_documents(input).collect::<Result<Vec<_>, _>>()?;
+
+    assert_eq!(
+        documents,
+        vec![
+            cjson!({c"a" => 1})?.into(),
+            cjson!([true])?.into(),
+            cjson!(42)?,
+            cjson!({})?.into(),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn assert_that_document_iteration_stops_on_errors() -> Result<(), Box<dyn std::error::Error>> {
+    let mut documents = CJson::iter_documents(b"1\n[2,\n x]\n{}");
+
+    assert_eq!(documents.next(), Some(Ok(cjson!(1)?)));
+    let Some(Err(Error::Parse(position))) = documents.next() else {
+        panic!("expected a parse error");
+    };
+    assert_eq!(position.offset(), 7);
+    assert_eq!(position.line(), 3);
+    assert_eq!(position.column(), 2);
+    assert_eq!(documents.next(), None);
+    Ok(())
+}
+
+#[test]
+fn assert_that_json_lines_can_be_written() -> Result<(), Box<dyn std::error::Error>> {
+    let values: Vec<CJson> = vec![cjson!({c"a" => 1})?.into(), cjson!([true, null])?.into()];
+    let mut output = String::new();
+    write_json_lines(&mut output, &values)?;
+
+    assert_eq!(output, "{\"a\":1}\n[true,null]\n");
+    let parsed = CJson::iter_documents(output.as_bytes()).collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(parsed, values);
+    Ok(())
+}
+
+// Printing
+#[test]
+fn assert_that_values_can_be_printed_into_buffers() -> Result<(), Box<dyn std::error::Error>> {
+    let cjson: CJson = cjson!({c"hello" => [1, true]})?.into();
+    let mut buffer = [0u8; 64];
+
+    assert_eq!(
+        cjson.print_into(&mut buffer, false)?,
+        c"{\"hello\":[1,true]}"
+    );
+    assert_eq!(
+        cjson.print_into(&mut buffer, true)?,
+        c"{\n\t\"hello\":\t[1, true]\n}"
+    );
+    Ok(())
+}
+
+#[test]
+fn assert_that_printing_into_small_buffers_fails() -> Result<(), Box<dyn std::error::Error>> {
+    let cjson: CJson = cjson!({c"hello" => c"world"})?.into();
+    let mut buffer = [0u8; 8];
+
+    assert_eq!(
+        cjson.print_into(&mut buffer, false),
+        Err(Error::BufferTooSmall)
+    );
+    Ok(())
+}
+
+#[test]
+fn assert_that_print_len_estimates_are_exact_for_integers() -> Result<(), Box<dyn std::error::Error>>
+{
+    let cjson: CJson = cjson!({
+        c"list" => [1, {-20}, 300, true, null, [], {}],
+        c"nested" => { c"key" => c"escaped \"value\"\n" },
+    })?
+    .into();
+
+    let compact = cjson.to_c_string()?;
+    let pretty = cjson.to_c_string_pretty()?;
+    assert_eq!(
+        cjson.estimated_print_len(false),
+        compact.to_bytes_with_nul().len()
+    );
+    assert_eq!(
+        cjson.estimated_print_len(true),
+        pretty.to_bytes_with_nul().len()
+    );
+    Ok(())
+}
+
+#[test]
+fn assert_that_values_can_be_printed_with_capacity() -> Result<(), Box<dyn std::error::Error>> {
+    let cjson: CJson = cjson!({c"hello" => [1.5, true]})?.into();
+
+    let compact = cjson.to_c_string_with_capacity(cjson.estimated_print_len(false), false)?;
+    assert_eq!(compact, cjson.to_c_string()?);
+    let pretty = cjson.to_c_string_with_capacity(0, true)?;
+    assert_eq!(pretty, cjson.to_c_string_pretty()?);
+    Ok(())
+}
+
+// Streaming
+#[test]
+fn assert_that_streamed_output_matches_cjson() -> Result<(), Box<dyn std::error::Error>> {
+    let cjson: CJson = r#"{
+        "numbers": [0, -7, 3000000000, 0.1, -2.5e-7, 1e300, 0.30000000000000004, 123456.789],
+        "strings": ["", "tab\tquote\"slash\\", "\u0001\u001f", "ünïcödé"],
+        "nested": {"empty_object": {}, "empty_array": [], "deep": {"list": [true, false, null]}},
+        "": "empty key"
+    }"#
+    .parse()?;
+
+    let mut compact = String::new();
+    cjson.to_fmt_writer(&mut compact, false)?;
+    assert_eq!(compact.as_bytes(), cjson.to_c_string()?.to_bytes());
+
+    let mut pretty = String::new();
+    cjson.to_fmt_writer(&mut pretty, true)?;
+    assert_eq!(pretty.as_bytes(), cjson.to_c_string_pretty()?.to_bytes());
+    Ok(())
+}
+
+#[test]
+fn assert_that_display_streams_compact_and_pretty_output() -> Result<(), Box<dyn std::error::Error>>
+{
+    let cjson: CJson = cjson!({c"a" => [1, 2], c"b" => {}})?.into();
+    assert_eq!(cjson.to_string(), r#"{"a":[1,2],"b":{}}"#);
+    assert_eq!(
+        format!("{cjson:#}"),
+        "{\n\t\"a\":\t[1, 2],\n\t\"b\":\t{\n\t}\n}"
+    );
+    Ok(())
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn assert_that_values_can_be_streamed_into_io_writers() -> Result<(), Box<dyn std::error::Error>> {
+    let cjson: CJson = cjson!([c"hello", 4.5, null])?.into();
+
+    let mut output = Vec::new();
+    cjson.to_io_writer(&mut output, false)?;
+    assert_eq!(output, br#"["hello",4.5,null]"#);
+    Ok(())
+}
+
+// Pretty-printing configuration
+#[test]
+fn assert_that_default_pretty_config_matches_cjson() -> Result<(), Box<dyn std::error::Error>> {
+    let cjson: CJson = r#"{"a": [1, {"b": [true, null]}], "c": {}, "d": "e"}"#.parse()?;
+
+    let mut output = String::new();
+    cjson.to_fmt_writer_with(&mut output, &PrettyConfig::default())?;
+    assert_eq!(output.as_bytes(), cjson.to_c_string_pretty()?.to_bytes());
+    Ok(())
+}
+
+#[test]
+fn assert_that_pretty_config_controls_layout() -> Result<(), Box<dyn std::error::Error>> {
+    let cjson: CJson =
+        r#"{"short": [1, 2], "long": [1, 2, 3], "nested": {"key": "value"}}"#.parse()?;
+    let config = PrettyConfig::new()
+        .indent("  ")
+        .newline("\r\n")
+        .key_value_separator(": ")
+        .max_inline_array_len(2)
+        .trailing_newline(true);
+
+    let mut output = String::new();
+    cjson.to_fmt_writer_with(&mut output, &config)?;
+    assert_eq!(
+        output,
+        "{\r\n  \"short\": [1, 2],\r\n  \"long\": [\r\n    1,\r\n    2,\r\n    3\r\n  ],\r\n  \"nested\": {\r\n    \"key\": \"value\"\r\n  }\r\n}\r\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn assert_that_display_with_uses_config_only_when_alternate(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cjson: CJson = cjson!({c"a" => [1, 2]})?.into();
+    let config = PrettyConfig::new().indent("  ").key_value_separator(": ");
+
+    assert_eq!(cjson.display_with(config).to_string(), r#"{"a":[1,2]}"#);
+    assert_eq!(
+        format!("{:#}", cjson.display_with(config)),
+        "{\n  \"a\": [1, 2]\n}"
+    );
+    Ok(())
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn assert_that_values_can_be_written_to_files() -> Result<(), Box<dyn std::error::Error>> {
+    let cjson: CJson = cjson!({c"a" => [1, 2]})?.into();
+    let path = std::env::temp_dir().join(format!("cjsonrs-{}.json", std::process::id()));
+    let config = PrettyConfig::new()
+        .indent("  ")
+        .key_value_separator(": ")
+        .trailing_newline(true);
+
+    cjson.write_to_file_with(&path, &config)?;
+    let written = std::fs::read_to_string(&path);
+    std::fs::remove_file(&path)?;
+    assert_eq!(written?, "{\n  \"a\": [1, 2]\n}\n");
+    Ok(())
+}
+
+// JSON Pointer
+#[test]
+fn assert_that_values_can_be_found_by_pointer() -> Result<(), Box<dyn std::error::Error>> {
+    let cjson: CJson =
+        r#"{"devices": [{"state": "off"}, {"state": "on"}], "a/b": {"m~n": 1}}"#.parse()?;
+
+    assert_eq!(cjson.pointer(c""), Some(&*cjson));
+    let state = cjson.pointer(c"/devices/1/state").unwrap();
+    assert_eq!(state.as_c_string(), Some(c"on"));
+    let escaped = cjson.pointer(c"/a~1b/m~0n").unwrap();
+    assert_eq!(escaped.as_number(), Some(1.0));
+
+    assert!(cjson.pointer(c"/devices/2").is_none());
+    assert!(cjson.pointer(c"/Devices").is_none());
+    assert!(cjson.pointer(c"devices").is_none());
+    assert!(cjson.pointer_case_insensitive(c"/Devices/0").is_some());
+    Ok(())
+}
+
+#[test]
+fn assert_that_values_can_be_mutated_by_pointer() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cjson: CJson = r#"{"devices": [{"state": "off"}]}"#.parse()?;
+
+    let device = cjson.pointer_mut(c"/devices/0").unwrap();
+    let mut device = device.as_mut_object().unwrap();
+    device.insert(c"state", CJson::string(c"on")?);
+
+    let state = cjson.pointer(c"/devices/0/state").unwrap();
+    assert_eq!(state.as_c_string(), Some(c"on"));
+    Ok(())
+}
+
+#[test]
+fn assert_that_pointer_tokens_roundtrip_escaping() {
+    let escaped = escape_pointer_token("~/a~1/").to_string();
+    assert_eq!(escaped, "~0~1a~01~1");
+    let unescaped = unescape_pointer_token(&escaped).unwrap();
+    assert_eq!(unescaped.to_string(), "~/a~1/");
+    assert!(unescape_pointer_token("a~").is_none());
+}
+
+#[test]
+fn assert_that_paths_can_be_computed_while_iterating() -> Result<(), Box<dyn std::error::Error>> {
+    let cjson: CJson = r#"{"a/b": [1, {"c~d": null}], "e": true}"#.parse()?;
+
+    let paths = (&*cjson)
+        .into_iter()
+        .flat_map(|child| child.into_iter())
+        .map(|item| cjson.path_to(item).map(|path| path.to_owned()))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        paths,
+        [Some(c"/a~1b/0".to_owned()), Some(c"/a~1b/1".to_owned())]
+    );
+    assert_eq!(cjson.path_to(&cjson).as_deref(), Some(c""));
+    Ok(())
+}
+
+#[test]
+fn assert_that_path_to_foreign_values_is_none() -> Result<(), Box<dyn std::error::Error>> {
+    let cjson: CJson = r#"{"a": 1}"#.parse()?;
+    let other: CJson = r#"{"a": 1}"#.parse()?;
+
+    assert!(cjson.path_to(cjson.pointer(c"/a").unwrap()).is_some());
+    assert!(cjson.path_to(other.pointer(c"/a").unwrap()).is_none());
+    Ok(())
+}
+
+// JSON Patch
+#[test]
+fn assert_that_diffs_can_be_applied() -> Result<(), Box<dyn std::error::Error>> {
+    let mut from: CJson = r#"{"devices": [{"state": "off"}], "name": "hub", "old": 1}"#.parse()?;
+    let mut to: CJson =
+        r#"{"devices": [{"state": "on"}, {"state": "off"}], "name": "hub"}"#.parse()?;
+
+    let patch = from.diff(&mut to)?;
+    assert!(!patch.is_empty());
+    from.apply_patch(&patch)?;
+    assert_eq!(from, to);
+    Ok(())
+}
+
+#[test]
+fn assert_that_equal_values_produce_empty_diffs() -> Result<(), Box<dyn std::error::Error>> {
+    let mut from: CJson = r#"{"a": [1, 2], "b": null}"#.parse()?;
+    let mut to: CJson = r#"{"b": null, "a": [1, 2]}"#.parse()?;
+
+    assert!(from.diff(&mut to)?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn assert_that_patch_errors_are_typed() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cjson: CJson = r#"{"a": 1}"#.parse()?;
+
+    let not_an_array: CJson = r#"{"op": "remove", "path": "/a"}"#.parse()?;
+    assert_eq!(cjson.apply_patch(&not_an_array), Err(PatchError::Malformed));
+    let unknown_op: CJson = r#"[{"op": "frobnicate", "path": "/a"}]"#.parse()?;
+    assert_eq!(cjson.apply_patch(&unknown_op), Err(PatchError::Malformed));
+    let failed_test: CJson = r#"[{"op": "test", "path": "/a", "value": 2}]"#.parse()?;
+    assert_eq!(cjson.apply_patch(&failed_test), Err(PatchError::TestFailed));
+    let missing_path: CJson = r#"[{"op": "remove", "path": "/b"}]"#.parse()?;
+    assert_eq!(
+        cjson.apply_patch(&missing_path),
+        Err(PatchError::MissingPath)
+    );
+    let root: CJson = r#"[{"op": "replace", "path": "", "value": 2}]"#.parse()?;
+    assert_eq!(cjson.apply_patch(&root), Err(PatchError::RootOperation));
+
+    // Operations are validated before cJSON reads them.
+    let numeric_from: CJson = r#"[{"op": "move", "from": 5, "path": "/x"}]"#.parse()?;
+    assert_eq!(cjson.apply_patch(&numeric_from), Err(PatchError::Malformed));
+    let missing_from: CJson = r#"[{"op": "copy", "path": "/x"}]"#.parse()?;
+    assert_eq!(
+        cjson.apply_patch_case_insensitive(&missing_from),
+        Err(PatchError::Malformed)
+    );
+    let missing_value: CJson = r#"[{"op": "add", "path": "/x"}]"#.parse()?;
+    assert_eq!(
+        cjson.apply_patch(&missing_value),
+        Err(PatchError::Malformed)
+    );
+    let numeric_path: CJson =
+        r#"[{"op": "remove", "path": "/a"}, {"op": "remove", "path": 1}]"#.parse()?;
+    assert_eq!(cjson.apply_patch(&numeric_path), Err(PatchError::Malformed));
+
+    assert_eq!(cjson, r#"{"a": 1}"#.parse::<CJson>()?);
+    Ok(())
+}
+
+#[test]
+fn assert_that_case_insensitive_patches_ignore_key_case() -> Result<(), Box<dyn std::error::Error>>
+{
+    let mut cjson: CJson = r#"{"State": "off"}"#.parse()?;
+    let patch: CJson = r#"[{"op": "replace", "path": "/state", "value": "on"}]"#.parse()?;
+
+    assert_eq!(cjson.apply_patch(&patch), Err(PatchError::MissingPath));
+    cjson.apply_patch_case_insensitive(&patch)?;
+    assert_eq!(cjson.pointer(c"/State").unwrap().as_c_string(), Some(c"on"));
+    Ok(())
+}
+
+// JSON Merge Patch
+#[test]
+fn assert_that_merge_patches_can_be_applied() -> Result<(), Box<dyn std::error::Error>> {
+    let config: CJson = r#"{"a": "b", "c": {"d": "e", "f": "g"}}"#.parse()?;
+    let patch: CJson = r#"{"a": "z", "c": {"f": null}, "h": [1]}"#.parse()?;
+
+    let config = config.merge_patch(&patch)?;
+    assert_eq!(
+        config,
+        r#"{"a": "z", "c": {"d": "e"}, "h": [1]}"#.parse::<CJson>()?
+    );
+    Ok(())
+}
+
+#[test]
+fn assert_that_merge_patches_can_replace_the_root() -> Result<(), Box<dyn std::error::Error>> {
+    let config: CJson = r#"{"a": 1}"#.parse()?;
+    let patch: CJson = r#"["replaced"]"#.parse()?;
+
+    let config = config.merge_patch(&patch)?;
+    assert_eq!(config, patch);
+    let object: CJson = r#"{"a": 1}"#.parse()?;
+    let config = config.merge_patch(&object)?;
+    assert_eq!(config, object);
+    Ok(())
+}
+
+#[test]
+fn assert_that_merge_diffs_roundtrip() -> Result<(), Box<dyn std::error::Error>> {
+    let mut from: CJson = r#"{"a": 1, "b": {"c": 2, "d": 3}}"#.parse()?;
+    let mut to: CJson = r#"{"a": 1, "b": {"c": 4}, "e": true}"#.parse()?;
+
+    let patch = from.merge_diff(&mut to)?;
+    assert_eq!(
+        patch,
+        r#"{"b": {"c": 4, "d": null}, "e": true}"#.parse::<CJson>()?
+    );
+    assert_eq!(from.merge_patch(&patch)?, to);
+    Ok(())
+}
+
+#[test]
+fn assert_that_equal_values_produce_empty_merge_diffs() -> Result<(), Box<dyn std::error::Error>> {
+    let mut from: CJson = r#"{"a": [1, 2]}"#.parse()?;
+    let mut to = from.duplicate()?;
+
+    let patch = from.merge_diff(&mut to)?;
+    assert_eq!(
+        patch.as_object().map(|object| object.is_empty()),
+        Some(true)
+    );
+    Ok(())
+}
+
+// Typed JSON Patch
+#[test]
+fn assert_that_json_patches_can_be_built_and_applied() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cjson: CJson = r#"{"a": {"b": 1}, "c": [1, 2]}"#.parse()?;
+    let (one, two) = (CJson::number(1)?, CJson::number(2)?);
+
+    let mut patch = JsonPatch::new()?;
+    patch.push(Op::Add {
+        path: c"/a/d",
+        value: &two,
+    })?;
+    patch.push(Op::Copy {
+        from: c"/a",
+        path: c"/e",
+    })?;
+    patch.push(Op::Move {
+        from: c"/c/0",
+        path: c"/c/-",
+    })?;
+    patch.push(Op::Remove { path: c"/a/b" })?;
+    patch.push(Op::Test {
+        path: c"/e/b",
+        value: &one,
+    })?;
+    assert_eq!(patch.len(), 5);
+
+    patch.apply(&mut cjson)?;
+    assert_eq!(
+        cjson,
+        r#"{"a": {"d": 2}, "c": [2, 1], "e": {"b": 1, "d": 2}}"#.parse::<CJson>()?
+    );
+    Ok(())
+}
+
+#[test]
+fn assert_that_json_patches_are_atomic() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cjson: CJson = r#"{"list": [{"a": 1}, {"b": 2}]}"#.parse()?;
+    let original = cjson.duplicate()?;
+
+    let mut patch = JsonPatch::new()?;
+    patch.push(Op::Remove { path: c"/b" })?;
+    patch.push(Op::Remove { path: c"/missing" })?;
+
+    let list = cjson.pointer_mut(c"/list/1").unwrap();
+    assert_eq!(patch.apply(list), Err(PatchError::MissingPath));
+    assert_eq!(cjson, original);
+    Ok(())
+}
+
+#[test]
+fn assert_that_json_patches_apply_to_nested_values() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cjson: CJson = r#"{"first": {"a": 1}, "second": {"b": 2}, "third": 3}"#.parse()?;
+    let yes = CJson::bool(true)?;
+    let mut patch = JsonPatch::new()?;
+    patch.push(Op::Replace {
+        path: c"/b",
+        value: &yes,
+    })?;
+
+    patch.apply(cjson.pointer_mut(c"/second").unwrap())?;
+    assert_eq!(
+        cjson.to_string(),
+        r#"{"first":{"a":1},"second":{"b":true},"third":3}"#
+    );
+    Ok(())
+}
+
+#[test]
+fn assert_that_json_patches_roundtrip_through_arrays() -> Result<(), Box<dyn std::error::Error>> {
+    let array: CJson = r#"[{"op": "copy", "from": "/a~1b", "path": "/c"}, {"op": "test", "path": "", "value": {}}]"#.parse()?;
+    let patch = JsonPatch::try_from(array.into_array().unwrap())?;
+
+    let ops = patch.iter().collect::<Vec<_>>();
+    assert_eq!(
+        ops[0],
+        Op::Copy {
+            from: c"/a~1b",
+            path: c"/c"
+        }
+    );
+    assert_eq!(ops[1].path(), c"");
+    assert!(ops[1].value().is_some_and(|value| value.is_object()));
+
+    let array = cjsonrs::CJsonArray::from(patch);
+    assert_eq!(array.len(), 2);
+    Ok(())
+}
+
+#[test]
+fn assert_that_invalid_json_patches_are_rejected() -> Result<(), Box<dyn std::error::Error>> {
+    for invalid in [
+        r#"[1]"#,
+        r#"[{"path": "/a"}]"#,
+        r#"[{"op": "add", "path": "/a"}]"#,
+        r#"[{"op": "move", "path": "/a"}]"#,
+        r#"[{"op": "remove", "path": "a"}]"#,
+        r#"[{"op": "remove", "path": "/~2"}]"#,
+        r#"[{"op": "frobnicate", "path": "/a"}]"#,
+    ] {
+        let array = invalid.parse::<CJson>()?.into_array().unwrap();
+        assert_eq!(
+            JsonPatch::try_from(array).err(),
+            Some(PatchError::Malformed)
+        );
+    }
+
+    let mut patch = JsonPatch::new()?;
+    assert_eq!(
+        patch.push(Op::Remove {
+            path: c"missing/slash"
+        }),
+        Err(Error::Patch(PatchError::Malformed))
+    );
+    Ok(())
+}
+
+// 64-bit integers
+#[test]
+fn assert_that_inexact_integers_are_rejected() -> Result<(), Box<dyn std::error::Error>> {
+    assert_eq!(CJson::i64(-(1 << 53))?.as_i64(), Some(-(1 << 53)));
+    assert_eq!(CJson::u64(1 << 63)?.as_u64(), Some(1 << 63));
+    assert_eq!(CJson::i64(i64::MAX), Err(Error::InexactNumber));
+    assert_eq!(CJson::u64(u64::MAX), Err(Error::InexactNumber));
+    assert_eq!(CJson::try_from((1u64 << 53) + 1), Err(Error::InexactNumber));
+    assert_eq!(CJson::try_from(-7i64)?, CJson::number(-7)?);
+    Ok(())
+}
+
+#[test]
+fn assert_that_lossless_integers_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    for n in [i64::MIN, -(1 << 53) - 1, -1, 0, 1 << 53, i64::MAX] {
+        let cjson = CJson::i64_lossless(n)?;
+        assert_eq!(cjson.as_i64(), Some(n));
+        assert_eq!(cjson.to_string(), n.to_string());
+        assert_eq!(cjson.to_c_string()?.to_str()?, n.to_string());
+    }
+
+    let cjson = CJson::u64_lossless(u64::MAX)?;
+    assert_eq!(cjson.as_number(), None);
+    assert_eq!(cjson.as_u64(), Some(u64::MAX));
+    assert_eq!(cjson.as_i64(), None);
+    assert_eq!(cjson.estimated_print_len(false), 21);
+    Ok(())
+}
+
+#[test]
+fn assert_that_integer_accessors_reject_non_integers() -> Result<(), Box<dyn std::error::Error>> {
+    assert_eq!(CJson::number(1.5)?.as_i64(), None);
+    assert_eq!(CJson::number(-1)?.as_u64(), None);
+    assert_eq!(CJson::number(f64::NAN)?.as_i64(), None);
+    assert_eq!(CJson::number(1e19)?.as_i64(), None);
+    assert_eq!(
+        CJson::number(1e19)?.as_u64(),
+        Some(10_000_000_000_000_000_000)
+    );
+    assert_eq!(CJson::string(c"42")?.as_i64(), None);
+    Ok(())
+}
+
+#[test]
+fn assert_that_integer_accessors_only_accept_json_integer_tokens(
+) -> Result<(), Box<dyn std::error::Error>> {
+    assert_eq!(CJson::raw_unvalidated(c"0")?.as_i64(), Some(0));
+    assert_eq!(CJson::raw_unvalidated(c"-0")?.as_i64(), Some(0));
+    assert_eq!(CJson::raw_unvalidated(c"-120")?.as_i64(), Some(-120));
+    for token in [c"+5", c"007", c"-01", c"", c"-", c"1e3", c"1.0", c" 1"] {
+        assert_eq!(CJson::raw_unvalidated(token)?.as_i64(), None, "{token:?}");
+    }
+    Ok(())
+}
+
+#[test]
+fn assert_that_checked_integer_accessors_respect_ranges() -> Result<(), Box<dyn std::error::Error>>
+{
+    let max = CJson::number(u32::MAX)?;
+    assert_eq!(max.as_u32(), Some(u32::MAX));
+    assert_eq!(max.as_i32(), None);
+    assert_eq!(max.as_usize(), Some(u32::MAX as usize));
+
+    let negative = CJson::number(i32::MIN)?;
+    assert_eq!(negative.as_i32(), Some(i32::MIN));
+    assert_eq!(negative.as_u32(), None);
+    assert_eq!(negative.as_usize(), None);
+
+    // cJSON saturates `valueint`, which must not leak through.
+    let large = CJson::number(1e12)?;
+    assert_eq!(large.as_i32(), None);
+    assert_eq!(large.as_i64(), Some(1_000_000_000_000));
+
+    assert_eq!(CJson::number(0.5)?.as_u32(), None);
+    assert_eq!(CJson::number(f64::INFINITY)?.as_usize(), None);
+    assert_eq!(CJson::null()?.as_i32(), None);
+    Ok(())
+}
+
+// Raw values
+#[test]
+fn assert_that_raw_values_are_printed_verbatim() -> Result<(), Box<dyn std::error::Error>> {
+    let raw = CJson::raw(c" {\"a\": [1, 2]} ")?;
+    assert!(raw.is_raw());
+    assert!(!raw.is_object());
+    assert_eq!(raw.as_raw(), Some(c" {\"a\": [1, 2]} "));
+    assert_eq!(format!("{raw:?}"), r#"Raw(" {\"a\": [1, 2]} ")"#);
+
+    let message: CJson = cjson!({ c"id" => 1, c"payload" => raw })?.into();
+    assert_eq!(message.to_string(), r#"{"id":1,"payload": {"a": [1, 2]} }"#);
+    assert_eq!(message.to_c_string()?.to_str()?, message.to_string());
+
+    assert!(matches!(CJson::raw(c"[1, 2"), Err(Error::Parse(_))));
+    assert!(matches!(CJson::raw(c"1 2"), Err(Error::Parse(_))));
+    assert!(matches!(CJson::raw(c""), Err(Error::Parse(_))));
+    assert_eq!(CJson::raw_unvalidated(c"[1,")?.to_string(), "[1,");
+    assert!(!CJson::null()?.is_raw());
+    Ok(())
+}
+
+// Canonical serialization
+#[test]
+fn assert_that_canonical_output_sorts_keys_by_utf16_code_units(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cjson: CJson = r#"{
+        "\u20ac": "Euro Sign",
+        "\r": "Carriage Return",
+        "\ufb33": "Hebrew Letter Dalet With Dagesh",
+        "1": "One",
+        "\ud83d\ude00": "Emoji: Grinning Face",
+        "\u0080": "Control",
+        "\u00f6": "Latin Small Letter O With Diaeresis"
+    }"#
+    .parse()?;
+
+    let keys = cjson
+        .canonical()
+        .to_string()
+        .parse::<CJson>()?
+        .into_iter()
+        .map(|item| item.as_c_string().unwrap().to_str().unwrap().to_owned())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        keys,
+        [
+            "Carriage Return",
+            "One",
+            "Control",
+            "Latin Small Letter O With Diaeresis",
+            "Euro Sign",
+            "Emoji: Grinning Face",
+            "Hebrew Letter Dalet With Dagesh",
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn assert_that_canonical_output_uses_shortest_numbers() -> Result<(), Box<dyn std::error::Error>> {
+    for (n, expected) in [
+        (0.0, "0"),
+        (-0.0, "0"),
+        (1.0, "1"),
+        (-1.5, "-1.5"),
+        (0.1, "0.1"),
+        (1e21, "1e+21"),
+        (1e20, "100000000000000000000"),
+        (1e-6, "0.000001"),
+        (1e-7, "1e-7"),
+        (5e-324, "5e-324"),
+        (f64::MAX, "1.7976931348623157e+308"),
+        (9.999999999999997e22, "9.999999999999997e+22"),
+        (333333333.3333332, "333333333.3333332"),
+    ] {
+        assert_eq!(CJson::number(n)?.canonical().to_string(), expected);
+    }
+    Ok(())
+}
+
+#[test]
+fn assert_that_equal_values_have_identical_canonical_output(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let a: CJson = r#"{"b": [1.0, "A"], "a": {"y": null, "x": true}}"#.parse()?;
+    let b: CJson = "{ \"a\": {\"x\": true, \"y\": null},\n \"b\": [1, \"A\"] }".parse()?;
+
+    assert_eq!(a.canonical().to_string(), b.canonical().to_string());
+    assert_eq!(
+        a.canonical().to_string(),
+        r#"{"a":{"x":true,"y":null},"b":[1,"A"]}"#
+    );
+    Ok(())
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn assert_that_canonical_output_can_be_streamed_into_io_writers(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cjson: CJson = r#"{"b": 2, "a": [0.5, 1e-7]}"#.parse()?;
+
+    let mut output = Vec::new();
+    cjson.to_canonical_io_writer(&mut output)?;
+    assert_eq!(output, br#"{"a":[0.5,1e-7],"b":2}"#);
+
+    let nan = CJson::number(f64::NAN)?;
+    assert!(nan.to_canonical_io_writer(&mut Vec::new()).is_err());
+    Ok(())
+}
+
+#[test]
+fn assert_that_non_canonicalizable_values_fail() -> Result<(), Box<dyn std::error::Error>> {
+    let duplicated: CJson = r#"{"a": 1, "a": 2}"#.parse()?;
+    let mut output = String::new();
+    assert!(write!(output, "{}", duplicated.canonical()).is_err());
+
+    let nan = CJson::number(f64::NAN)?;
+    assert!(write!(output, "{}", nan.canonical()).is_err());
+    Ok(())
+}
+
+#[test]
+fn assert_that_sort_keys_sorts_nested_objects_when_recursive(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let json = r#"{"b": {"d": 1, "c": 2}, "a": [{"f": 3, "e": 4}]}"#;
+
+    let mut object = json.parse::<CJson>()?.into_object().unwrap();
+    object.sort_keys(false);
+    assert_eq!(
+        object.to_string(),
+        r#"{"a":[{"f":3,"e":4}],"b":{"d":1,"c":2}}"#
+    );
+
+    let mut object = json.parse::<CJson>()?.into_object().unwrap();
+    object.sort_keys(true);
+    assert_eq!(
+        object.to_string(),
+        r#"{"a":[{"e":4,"f":3}],"b":{"c":2,"d":1}}"#
+    );
+    Ok(())
+}
+
+#[test]
+fn assert_that_sort_keys_case_insensitive_ignores_ascii_case(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let json = r#"{"b": {"D": 1, "c": 2}, "A": 3, "C": 4}"#;
+
+    let mut object = json.parse::<CJson>()?.into_object().unwrap();
+    object.sort_keys(true);
+    assert_eq!(object.to_string(), r#"{"A":3,"C":4,"b":{"D":1,"c":2}}"#);
+
+    let mut object = json.parse::<CJson>()?.into_object().unwrap();
+    object.sort_keys_case_insensitive(true);
+    assert_eq!(object.to_string(), r#"{"A":3,"b":{"c":2,"D":1},"C":4}"#);
+    Ok(())
+}
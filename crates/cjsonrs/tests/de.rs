@@ -1 +1,420 @@
+#![cfg(feature = "serde")]
 
+use std::collections::BTreeMap;
+
+use cjsonrs::cjson;
+use cjsonrs::serde::from_cjson;
+use cjsonrs::serde::to_cjson;
+use cjsonrs::serde::to_cjson_lossless;
+use cjsonrs::serde::Deserializer;
+use cjsonrs::serde::LosslessSeed;
+use cjsonrs::serde::RawJson;
+use cjsonrs::CJson;
+use core::error::Error;
+use serde::de::DeserializeSeed;
+use serde::{Deserialize, Serialize};
+
+#[test]
+fn assert_that_booleans_can_be_deserialized_from_cjson() -> Result<(), Box<dyn Error>> {
+    let cjson = cjson!(true)?;
+    assert!(from_cjson::<bool>(&cjson)?);
+    Ok(())
+}
+
+#[test]
+fn assert_that_numbers_can_be_deserialized_from_cjson() -> Result<(), Box<dyn Error>> {
+    let (negative, positive, fractional) = (cjson!(-42)?, cjson!(42)?, cjson!(4.2)?);
+    assert_eq!(from_cjson::<i32>(&negative)?, -42);
+    assert_eq!(from_cjson::<u8>(&positive)?, 42);
+    assert_eq!(from_cjson::<f64>(&fractional)?, 4.2);
+    Ok(())
+}
+
+#[test]
+fn assert_that_fractional_numbers_cannot_be_deserialized_into_integers(
+) -> Result<(), Box<dyn Error>> {
+    let cjson = cjson!(4.2)?;
+    assert!(from_cjson::<i32>(&cjson).is_err());
+    Ok(())
+}
+
+#[test]
+fn assert_that_strings_can_be_deserialized_from_cjson() -> Result<(), Box<dyn Error>> {
+    let cjson = cjson!(c"hello")?;
+    assert_eq!(from_cjson::<String>(&cjson)?, "hello");
+    Ok(())
+}
+
+#[test]
+fn assert_that_arrays_can_be_deserialized_from_cjson() -> Result<(), Box<dyn Error>> {
+    let cjson: CJson = cjson!([1, 2, 3])?.into();
+    assert_eq!(from_cjson::<Vec<u32>>(&cjson)?, vec![1, 2, 3]);
+    Ok(())
+}
+
+#[test]
+fn assert_that_tuples_can_be_deserialized_from_cjson() -> Result<(), Box<dyn Error>> {
+    let cjson: CJson = cjson!([c"hello", 42])?.into();
+    assert_eq!(
+        from_cjson::<(String, i32)>(&cjson)?,
+        ("hello".to_string(), 42)
+    );
+    Ok(())
+}
+
+#[test]
+fn assert_that_maps_can_be_deserialized_from_cjson() -> Result<(), Box<dyn Error>> {
+    let cjson: CJson = cjson!({ c"a" => 1, c"b" => 2 })?.into();
+    let expected = BTreeMap::from([("a".to_string(), 1), ("b".to_string(), 2)]);
+    assert_eq!(from_cjson::<BTreeMap<String, i32>>(&cjson)?, expected);
+    Ok(())
+}
+
+#[test]
+fn assert_that_structs_can_be_deserialized_from_cjson() -> Result<(), Box<dyn Error>> {
+    let cjson: CJson = cjson!({
+        c"hello" => c"world",
+        c"answer" => 42,
+        c"nested" => { c"list" => [true, false] },
+    })?
+    .into();
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+    struct Nested {
+        list: Vec<bool>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+    struct Object {
+        hello: String,
+        answer: i32,
+        nested: Nested,
+    }
+
+    assert_eq!(
+        from_cjson::<Object>(&cjson)?,
+        Object {
+            hello: "world".to_string(),
+            answer: 42,
+            nested: Nested {
+                list: vec![true, false]
+            },
+        }
+    );
+    Ok(())
+}
+
+#[test]
+fn assert_that_cjson_can_be_deserialized_from_cjson() -> Result<(), Box<dyn Error>> {
+    let cjson: CJson = cjson!({
+        c"list" => [1, c"two", null, { c"three" => true }],
+    })?
+    .into();
+    assert_eq!(from_cjson::<CJson>(&cjson)?, cjson);
+    Ok(())
+}
+
+#[test]
+fn assert_that_serialized_values_roundtrip() -> Result<(), Box<dyn Error>> {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Reading {
+        sensor: String,
+        values: Vec<f64>,
+    }
+
+    let reading = Reading {
+        sensor: "temperature".to_string(),
+        values: vec![21.5, 22.0, -3.25],
+    };
+    let cjson = to_cjson(&reading)?;
+    assert_eq!(from_cjson::<Reading>(&cjson)?, reading);
+    Ok(())
+}
+
+#[test]
+fn assert_that_options_can_be_deserialized_from_cjson() -> Result<(), Box<dyn Error>> {
+    let cjson: CJson = cjson!([1, null])?.into();
+    assert_eq!(from_cjson::<Vec<Option<i32>>>(&cjson)?, vec![Some(1), None]);
+    Ok(())
+}
+
+#[test]
+fn assert_that_unit_can_be_deserialized_from_cjson() -> Result<(), Box<dyn Error>> {
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+    struct Object;
+
+    let cjson = cjson!(null)?;
+    assert_eq!(from_cjson::<Object>(&cjson)?, Object);
+    Ok(())
+}
+
+#[test]
+fn assert_that_newtype_structs_can_be_deserialized_from_cjson() -> Result<(), Box<dyn Error>> {
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+    struct Object(String);
+
+    let cjson = cjson!(c"hello")?;
+    assert_eq!(from_cjson::<Object>(&cjson)?, Object("hello".to_string()));
+    Ok(())
+}
+
+#[test]
+fn assert_that_tuple_structs_can_be_deserialized_from_cjson() -> Result<(), Box<dyn Error>> {
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+    struct Object(String, i32);
+
+    let cjson: CJson = cjson!([c"hello", 42])?.into();
+    assert_eq!(
+        from_cjson::<Object>(&cjson)?,
+        Object("hello".to_string(), 42)
+    );
+    Ok(())
+}
+
+#[test]
+fn assert_that_unit_variant_can_be_deserialized_from_cjson() -> Result<(), Box<dyn Error>> {
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+    enum Object {
+        Variant,
+    }
+
+    let cjson = cjson!(c"Variant")?;
+    assert_eq!(from_cjson::<Object>(&cjson)?, Object::Variant);
+    Ok(())
+}
+
+#[test]
+fn assert_that_newtype_variant_can_be_deserialized_from_cjson() -> Result<(), Box<dyn Error>> {
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+    enum Object {
+        Variant(u32),
+    }
+
+    let cjson: CJson = cjson!({ c"Variant" => 42 })?.into();
+    assert_eq!(from_cjson::<Object>(&cjson)?, Object::Variant(42));
+    Ok(())
+}
+
+#[test]
+fn assert_that_tuple_variant_can_be_deserialized_from_cjson() -> Result<(), Box<dyn Error>> {
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+    enum Object {
+        Variant(String, i32),
+    }
+
+    let cjson: CJson = cjson!({ c"Variant" => [c"hello", 42] })?.into();
+    assert_eq!(
+        from_cjson::<Object>(&cjson)?,
+        Object::Variant("hello".to_string(), 42)
+    );
+    Ok(())
+}
+
+#[test]
+fn assert_that_struct_variant_can_be_deserialized_from_cjson() -> Result<(), Box<dyn Error>> {
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+    enum Object {
+        Variant { hello: String, answer: i32 },
+    }
+
+    let cjson: CJson = cjson!({
+        c"Variant" => {
+            c"hello" => c"world",
+            c"answer" => 42,
+        },
+    })?
+    .into();
+    assert_eq!(
+        from_cjson::<Object>(&cjson)?,
+        Object::Variant {
+            hello: "world".to_string(),
+            answer: 42,
+        }
+    );
+    Ok(())
+}
+
+#[test]
+fn assert_that_enums_with_multiple_keys_are_rejected() -> Result<(), Box<dyn Error>> {
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+    enum Object {
+        A(u32),
+        B(u32),
+    }
+
+    let cjson: CJson = cjson!({ c"A" => 1, c"B" => 2 })?.into();
+    assert!(from_cjson::<Object>(&cjson).is_err());
+    Ok(())
+}
+
+#[test]
+fn assert_that_integer_keyed_maps_roundtrip() -> Result<(), Box<dyn Error>> {
+    let map = BTreeMap::from([(1u32, "one".to_string()), (2, "two".to_string())]);
+    let cjson = to_cjson(&map)?;
+    assert_eq!(from_cjson::<BTreeMap<u32, String>>(&cjson)?, map);
+    Ok(())
+}
+
+#[test]
+fn assert_that_strings_can_be_borrowed_from_cjson() -> Result<(), Box<dyn Error>> {
+    #[derive(Deserialize, Debug, PartialEq, Eq)]
+    struct Message<'a> {
+        topic: &'a str,
+        payload: Vec<&'a str>,
+    }
+
+    let cjson: CJson = r#"{"topic": "sensors/1", "payload": ["on", "off"]}"#.parse()?;
+    let message: Message = from_cjson(&cjson)?;
+
+    assert_eq!(
+        message,
+        Message {
+            topic: "sensors/1",
+            payload: vec!["on", "off"],
+        }
+    );
+    let object = cjson.as_object().unwrap();
+    let topic = object.get(c"topic").unwrap();
+    assert_eq!(
+        message.topic.as_ptr(),
+        topic.as_c_string().unwrap().as_ptr().cast()
+    );
+    Ok(())
+}
+
+#[test]
+fn assert_that_keys_can_be_borrowed_from_cjson() -> Result<(), Box<dyn Error>> {
+    let cjson: CJson = r#"{"hello": "world"}"#.parse()?;
+    let map: BTreeMap<&str, &str> = from_cjson(&cjson)?;
+
+    assert_eq!(map, BTreeMap::from([("hello", "world")]));
+    Ok(())
+}
+
+#[test]
+fn assert_that_64_bit_integers_round_trip_through_cjson() -> Result<(), Box<dyn Error>> {
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+    struct Message {
+        id: u64,
+        offset: i64,
+    }
+
+    let message = Message {
+        id: (1 << 53) + 1,
+        offset: i64::MIN,
+    };
+    let cjson = to_cjson_lossless(&message)?;
+    assert_eq!(from_cjson::<Message>(&cjson)?, message);
+
+    let value = LosslessSeed.deserialize(Deserializer(&cjson))?;
+    assert_eq!(value.to_string(), cjson.to_string());
+    Ok(())
+}
+
+#[test]
+fn assert_that_inexact_integers_are_rejected_when_deserializing_cjson() -> Result<(), Box<dyn Error>>
+{
+    let cjson = to_cjson_lossless(&[u64::MAX])?;
+    assert!(from_cjson::<CJson>(&cjson).is_err());
+
+    let cjson = to_cjson(&[1u64 << 53])?;
+    assert_eq!(from_cjson::<CJson>(&cjson)?, cjson);
+    Ok(())
+}
+
+#[test]
+fn assert_that_raw_json_can_be_captured_from_cjson() -> Result<(), Box<dyn Error>> {
+    #[derive(Deserialize)]
+    struct Message {
+        id: u32,
+        payload: RawJson,
+        cached: RawJson,
+    }
+
+    let mut cjson: CJson = r#"{"id": 7, "payload": {"a": [1, "b"]}}"#.parse()?;
+    cjson
+        .as_mut_object()
+        .unwrap()
+        .insert(c"cached", CJson::raw(c"[1,  2]")?);
+
+    let message: Message = from_cjson(&cjson)?;
+    assert_eq!(message.id, 7);
+    assert_eq!(message.payload.get(), r#"{"a":[1,"b"]}"#);
+    assert_eq!(message.cached.get(), "[1,  2]");
+
+    // Other deserializers hand the newtype over as is, so a string is captured.
+    #[derive(Deserialize, Debug, PartialEq)]
+    #[serde(untagged)]
+    enum Payload {
+        Raw(RawJson),
+    }
+
+    let cjson = cjson!(c"[1]")?;
+    assert_eq!(
+        from_cjson::<Payload>(&cjson)?,
+        Payload::Raw(RawJson::from_string_unvalidated("[1]".to_owned()))
+    );
+    Ok(())
+}
+
+#[test]
+fn assert_that_raw_json_rejects_values_that_cannot_be_printed() -> Result<(), Box<dyn Error>> {
+    // cJSON parses strings that are not valid UTF-8, but cannot print them.
+    let cjson = CJson::from_slice(b"{\"payload\": \"\xff\"}")?;
+    assert!(from_cjson::<BTreeMap<String, RawJson>>(&cjson).is_err());
+    assert!(from_cjson::<RawJson>(&cjson).is_err());
+    Ok(())
+}
+
+#[test]
+fn assert_that_raw_json_can_be_deserialized_into_any_type() -> Result<(), Box<dyn Error>> {
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Message {
+        flag: bool,
+        list: Vec<u32>,
+        topic: String,
+        kind: Kind,
+        untagged: Untagged,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    enum Kind {
+        Reading { value: f64 },
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    #[serde(untagged)]
+    enum Untagged {
+        Number(f64),
+        Text(String),
+    }
+
+    let mut cjson: CJson = "{}".parse()?;
+    let mut object = cjson.as_mut_object().unwrap();
+    object.insert(c"flag", CJson::raw(c"true")?);
+    object.insert(c"list", CJson::raw(c"[1, 2]")?);
+    object.insert(c"topic", CJson::raw(c"\"sensors\"")?);
+    object.insert(c"kind", CJson::raw(c"{\"Reading\": {\"value\": 1.5}}")?);
+    object.insert(c"untagged", CJson::raw(c"\"text\"")?);
+
+    assert_eq!(
+        from_cjson::<Message>(&cjson)?,
+        Message {
+            flag: true,
+            list: vec![1, 2],
+            topic: "sensors".to_string(),
+            kind: Kind::Reading { value: 1.5 },
+            untagged: Untagged::Text("text".to_string()),
+        }
+    );
+
+    let expected: CJson =
+        r#"{"flag":true,"list":[1,2],"topic":"sensors","kind":{"Reading":{"value":1.5}},"untagged":"text"}"#
+            .parse()?;
+    assert_eq!(from_cjson::<CJson>(&cjson)?, expected);
+
+    // Strings cannot be borrowed from the parsed fragment.
+    let raw = CJson::raw(c"\"text\"")?;
+    assert!(from_cjson::<&str>(&raw).is_err());
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use core::ffi::c_void;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+use cjsonrs::hooks::Allocator;
+use cjsonrs::CJson;
+use cjsonrs::HooksError;
+
+extern "C" {
+    fn malloc(size: usize) -> *mut c_void;
+    fn free(ptr: *mut c_void);
+}
+
+struct CountingAllocator {
+    allocations: AtomicUsize,
+    frees: AtomicUsize,
+}
+
+unsafe impl Allocator for CountingAllocator {
+    fn malloc(&self, size: usize) -> *mut c_void {
+        self.allocations.fetch_add(1, Ordering::Relaxed);
+        unsafe { malloc(size) }
+    }
+
+    unsafe fn free(&self, ptr: *mut c_void) {
+        self.frees.fetch_add(1, Ordering::Relaxed);
+        free(ptr)
+    }
+}
+
+static ALLOCATOR: CountingAllocator = CountingAllocator {
+    allocations: AtomicUsize::new(0),
+    frees: AtomicUsize::new(0),
+};
+
+// Hooks are process-wide, so every step runs within a single test.
+#[cfg(not(feature = "rust-alloc"))]
+#[test]
+fn assert_that_hooks_are_installed_once_and_only_while_unused(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cjson: CJson = r#"{"hello": ["world"]}"#.parse()?;
+    let printed = cjson.to_c_string()?;
+    assert_eq!(cjsonrs::hooks::install(&ALLOCATOR), Err(HooksError::InUse));
+
+    drop(cjson);
+    assert_eq!(cjsonrs::hooks::install(&ALLOCATOR), Err(HooksError::InUse));
+
+    drop(printed);
+    assert_eq!(cjsonrs::hooks::install(&ALLOCATOR), Ok(()));
+    assert_eq!(ALLOCATOR.allocations.load(Ordering::Relaxed), 0);
+
+    let cjson: CJson = r#"{"hello": ["world"]}"#.parse()?;
+    let printed = cjson.to_c_string()?;
+    assert_eq!(printed.to_str()?, r#"{"hello":["world"]}"#);
+    assert!(ALLOCATOR.allocations.load(Ordering::Relaxed) > 0);
+
+    drop((cjson, printed));
+    assert_eq!(
+        ALLOCATOR.allocations.load(Ordering::Relaxed),
+        ALLOCATOR.frees.load(Ordering::Relaxed)
+    );
+    assert_eq!(
+        cjsonrs::hooks::install(&ALLOCATOR),
+        Err(HooksError::AlreadyInstalled)
+    );
+    Ok(())
+}
+
+#[cfg(feature = "rust-alloc")]
+#[test]
+fn assert_that_rust_alloc_is_installed_on_first_use() -> Result<(), Box<dyn std::error::Error>> {
+    use cjsonrs::hooks::RustAlloc;
+
+    let cjson: CJson = r#"{"hello": ["world", 1.5, null]}"#.parse()?;
+    assert_eq!(
+        cjson.to_c_string()?.to_str()?,
+        r#"{"hello":["world",1.5,null]}"#
+    );
+    assert_eq!(
+        cjsonrs::hooks::install(&RustAlloc),
+        Err(HooksError::AlreadyInstalled)
+    );
+    assert_eq!(
+        cjsonrs::hooks::install(&ALLOCATOR),
+        Err(HooksError::AlreadyInstalled)
+    );
+    Ok(())
+}
@@ -0,0 +1,72 @@
+#![cfg(feature = "std")]
+
+use core::ffi::c_void;
+
+use cjsonrs::hooks::Allocator;
+use cjsonrs::hooks::Counting;
+use cjsonrs::CJson;
+use cjsonrs::Error;
+
+extern "C" {
+    fn malloc(size: usize) -> *mut c_void;
+    fn free(ptr: *mut c_void);
+}
+
+struct Libc;
+
+unsafe impl Allocator for Libc {
+    fn malloc(&self, size: usize) -> *mut c_void {
+        unsafe { malloc(size) }
+    }
+
+    unsafe fn free(&self, ptr: *mut c_void) {
+        free(ptr)
+    }
+}
+
+static COUNTING: Counting<Libc> = Counting::new(Libc);
+
+// Hooks are process-wide, so every step runs within a single test.
+#[test]
+fn assert_that_allocations_are_counted_and_limited() -> Result<(), Box<dyn std::error::Error>> {
+    #[cfg(not(feature = "rust-alloc"))]
+    assert_eq!(
+        CJson::from_slice_with_limit(b"1", usize::MAX),
+        Err(Error::HooksRequired)
+    );
+
+    cjsonrs::hooks::install(&COUNTING)?;
+    assert_eq!(COUNTING.stats().allocations(), 0);
+
+    let cjson: CJson = r#"{"hello": ["world", 1, 2, 3]}"#.parse()?;
+    let stats = COUNTING.stats();
+    assert!(stats.live_bytes() > 0);
+    assert_eq!(stats.peak_bytes(), stats.live_bytes());
+
+    drop(cjson);
+    let stats = COUNTING.stats();
+    assert_eq!(stats.live_bytes(), 0);
+    assert!(stats.peak_bytes() > 0);
+    assert_eq!(stats.allocations(), stats.frees());
+
+    let payload = format!("[{}0]", "0,".repeat(1000));
+    assert_eq!(
+        CJson::from_slice_with_limit(payload.as_bytes(), 1024),
+        Err(Error::Allocation)
+    );
+    // Partially parsed values are freed on failure.
+    assert_eq!(COUNTING.stats().live_bytes(), 0);
+
+    let cjson = CJson::from_slice_with_limit(payload.as_bytes(), 1024 * 1024)?;
+    assert_eq!(cjson.as_array().map(|array| array.len()), Some(1001));
+
+    // The limit only applies while parsing.
+    let printed = cjson.to_c_string()?;
+    assert_eq!(printed.to_bytes(), payload.as_bytes());
+
+    assert!(matches!(
+        CJson::from_slice_with_limit(b"[1, 2", 1024 * 1024),
+        Err(Error::Parse(_))
+    ));
+    Ok(())
+}
@@ -2,6 +2,9 @@
 
 use cjsonrs::cjson;
 use cjsonrs::serde::to_cjson;
+use cjsonrs::serde::to_cjson_lossless;
+use cjsonrs::serde::RawJson;
+use cjsonrs::CJson;
 use core::error::Error;
 use serde::{Deserialize, Serialize};
 
@@ -92,7 +95,7 @@ fn assert_that_struct_variant_can_be_serialized_into_cjson() -> Result<(), Box<d
 
 #[test]
 fn assert_that_unit_variant_can_be_serialized_into_cjson() -> Result<(), Box<dyn Error>> {
-    let expected = cjson!(c"Variant")?.into();
+    let expected = cjson!(c"Variant")?;
 
     #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
     enum Object {
@@ -149,3 +152,68 @@ fn assert_that_booleans_can_be_serialized_into_cjson() -> Result<(), Box<dyn Err
     assert_eq!(to_cjson(&true)?, expected);
     Ok(())
 }
+
+#[test]
+fn assert_that_inexact_64_bit_integers_are_rejected_by_default() -> Result<(), Box<dyn Error>> {
+    #[derive(Serialize)]
+    struct Message {
+        id: u64,
+    }
+
+    let inexact = cjsonrs::serde::Error::CJson(cjsonrs::Error::InexactNumber);
+    assert_eq!(to_cjson(&Message { id: u64::MAX }), Err(inexact.clone()));
+    assert_eq!(to_cjson(&(i64::MIN + 1)), Err(inexact));
+    assert_eq!(
+        to_cjson(&Message { id: 1 << 53 })?.to_string(),
+        r#"{"id":9007199254740992}"#
+    );
+    Ok(())
+}
+
+#[test]
+fn assert_that_64_bit_integers_can_be_serialized_losslessly() -> Result<(), Box<dyn Error>> {
+    #[derive(Serialize)]
+    struct Message {
+        id: u64,
+        offset: i64,
+        small: u64,
+    }
+
+    let cjson = to_cjson_lossless(&Message {
+        id: u64::MAX,
+        offset: i64::MIN + 1,
+        small: 42,
+    })?;
+    assert_eq!(
+        cjson.to_string(),
+        r#"{"id":18446744073709551615,"offset":-9223372036854775807,"small":42}"#
+    );
+    assert_eq!(cjson.to_c_string()?.to_str()?, cjson.to_string());
+    Ok(())
+}
+
+#[test]
+fn assert_that_raw_json_is_spliced_verbatim() -> Result<(), Box<dyn Error>> {
+    #[derive(Serialize)]
+    struct Message {
+        id: u32,
+        payload: RawJson,
+    }
+
+    let payload = RawJson::from_string(r#"{"cached": [1, 2.50]}"#.to_owned())?;
+    let cjson = to_cjson(&Message { id: 7, payload })?;
+    assert!(cjson.as_object().unwrap().get(c"payload").unwrap().is_raw());
+    assert_eq!(
+        cjson.to_string(),
+        r#"{"id":7,"payload":{"cached": [1, 2.50]}}"#
+    );
+
+    // Raw values serialize back into raw values.
+    assert_eq!(to_cjson(&cjson)?.to_string(), cjson.to_string());
+    assert_eq!(to_cjson(&CJson::raw(c"[true]")?)?.as_raw(), Some(c"[true]"));
+
+    assert!(RawJson::from_string("[1, 2".to_owned()).is_err());
+    assert!(RawJson::from_string("1 2".to_owned()).is_err());
+    assert!(RawJson::from_string("\"a\0\"".to_owned()).is_err());
+    Ok(())
+}